@@ -0,0 +1,241 @@
+use log::warn;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use walkdir::WalkDir;
+use wildmatch::WildMatch;
+
+/// Single file to convert
+#[derive(Debug)]
+pub struct Job {
+    pub input: PathBuf,
+    pub output_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Success,
+    Warning, // Converted with warnings
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub status: Status,
+    pub outputs: Vec<PathBuf>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>, // Why file failed, including validation errors
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    pub total: usize,
+    pub succeeded: usize,
+    pub warned: usize,
+    pub failed: usize,
+    pub files: Vec<FileReport>,
+}
+
+/// Warnings of single file, logged as they're added
+pub struct Warnings<'a> {
+    input: &'a Path,
+    pub messages: Vec<String>,
+}
+
+impl<'a> Warnings<'a> {
+    pub fn new(input: &'a Path) -> Self {
+        Warnings {
+            input,
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn push<T: ToString>(&mut self, message: T) {
+        let message = message.to_string();
+
+        warn!("\"{}\": {message}", self.input.display());
+        self.messages.push(message);
+    }
+}
+
+// Output paths currently claimed by jobs
+static CLAIMED_PATHS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+static PATHS_RELEASED: Condvar = Condvar::new();
+
+/// Exclusive access to output paths, so files shared between jobs (textures) are checked + written by one job at a time
+pub struct OutputClaim {
+    paths: Vec<PathBuf>,
+}
+
+impl OutputClaim {
+    /// Blocks until none of the paths are claimed by another job
+    pub fn new(paths: &[PathBuf]) -> Self {
+        let mut claimed = CLAIMED_PATHS.lock().unwrap_or_else(|err| err.into_inner());
+
+        while paths.iter().any(|p| claimed.contains(p)) {
+            claimed = PATHS_RELEASED.wait(claimed).unwrap_or_else(|err| err.into_inner());
+        }
+
+        claimed.extend(paths.iter().cloned());
+
+        OutputClaim {
+            paths: paths.to_owned(),
+        }
+    }
+}
+
+impl Drop for OutputClaim {
+    fn drop(&mut self) {
+        let mut claimed = CLAIMED_PATHS.lock().unwrap_or_else(|err| err.into_inner());
+
+        for path in self.paths.iter() {
+            claimed.remove(path);
+        }
+
+        PATHS_RELEASED.notify_all();
+    }
+}
+
+/// Finds files to convert, directories are searched recursively + mirrored into output dir
+pub fn find_jobs(input: &Path, output_dir: &Path, pattern: &str) -> Result<Vec<Job>, walkdir::Error> {
+    if !input.is_dir() {
+        return Ok(vec![Job {
+            input: input.to_owned(),
+            output_dir: output_dir.to_owned(),
+        }]);
+    }
+
+    let pattern = WildMatch::new_case_insensitive(pattern);
+    let mut jobs = Vec::new();
+
+    for entry in WalkDir::new(input).sort_by_file_name() {
+        let entry = entry?;
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        // Match with same separators on every platform
+        let relative_path = entry.path().strip_prefix(input).unwrap_or(entry.path());
+        let relative_str = relative_path.to_string_lossy().replace('\\', "/");
+
+        if !pattern.matches(&relative_str) {
+            continue;
+        }
+
+        jobs.push(Job {
+            input: entry.path().to_owned(),
+            output_dir: match relative_path.parent() {
+                Some(parent) => output_dir.join(parent),
+                None => output_dir.to_owned(),
+            },
+        });
+    }
+
+    Ok(jobs)
+}
+
+impl BatchReport {
+    pub fn new(files: Vec<FileReport>) -> Self {
+        let count = |status| files.iter().filter(|f| f.status == status).count();
+
+        BatchReport {
+            total: files.len(),
+            succeeded: count(Status::Success),
+            warned: count(Status::Warning),
+            failed: count(Status::Error),
+            files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Empty dir unique to test
+    fn create_temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ddm2gltf_{}_{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn find_jobs_test() {
+        let input = create_temp_dir("find_jobs");
+        let output = Path::new("out");
+
+        for file in ["a.ddm", "notes.txt", "sub/B.DDM", "sub/deep/c.ddm", "sub/deep/c.dds"] {
+            let path = input.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, []).unwrap();
+        }
+
+        // Recursive + case-insensitive, output dirs mirror input
+        let jobs = find_jobs(&input, output, "*.ddm").unwrap();
+        let found: Vec<_> = jobs
+            .iter()
+            .map(|j| (j.input.strip_prefix(&input).unwrap().to_owned(), j.output_dir.to_owned()))
+            .collect();
+
+        assert_eq!(vec![
+            (PathBuf::from("a.ddm"), PathBuf::from("out")),
+            (PathBuf::from("sub/B.DDM"), PathBuf::from("out/sub")),
+            (PathBuf::from("sub/deep/c.ddm"), PathBuf::from("out/sub/deep")),
+        ], found);
+
+        // Pattern is matched against relative path
+        let jobs = find_jobs(&input, output, "SUB/deep/*").unwrap();
+        assert_eq!(2, jobs.len());
+
+        // Files are converted to output dir as-is
+        let file = input.join("sub/B.DDM");
+        let jobs = find_jobs(&file, output, "*.txt").unwrap();
+        assert_eq!(1, jobs.len());
+        assert_eq!((file.as_path(), output), (jobs[0].input.as_path(), jobs[0].output_dir.as_path()));
+
+        std::fs::remove_dir_all(&input).unwrap();
+    }
+
+    #[test]
+    fn batch_report_new_test() {
+        let file = |status| FileReport {
+            input: PathBuf::from("model.ddm"),
+            status,
+            outputs: Vec::new(),
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let report = BatchReport::new(vec![file(Status::Success), file(Status::Error), file(Status::Warning), file(Status::Success)]);
+        assert_eq!((4, 2, 1, 1), (report.total, report.succeeded, report.warned, report.failed));
+    }
+
+    #[test]
+    fn output_claim_test() {
+        let shared = PathBuf::from("output_claim/shared.png");
+        let claim = OutputClaim::new(&[PathBuf::from("output_claim/a.gltf"), shared.to_owned()]);
+
+        // Unrelated paths aren't blocked
+        drop(OutputClaim::new(&[PathBuf::from("output_claim/b.gltf")]));
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let _claim = OutputClaim::new(&[PathBuf::from("output_claim/b.gltf"), shared]);
+            sender.send(()).unwrap();
+        });
+
+        // Waits for shared path to be released
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(receiver.try_recv().is_err());
+
+        drop(claim);
+        receiver.recv().unwrap();
+        handle.join().unwrap();
+    }
+}
\ No newline at end of file
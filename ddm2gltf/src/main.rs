@@ -1,78 +1,325 @@
+mod batch;
+
+use batch::*;
+use clap::{ArgAction, Parser, ValueEnum};
 use gltf_json as json;
 use grim_gltf::*;
+use log::{debug, error, info, trace, LevelFilter};
 use offbeat::*;
-use std::collections::{BTreeMap, HashMap};
-use std::env;
+use rayon::prelude::*;
+use simplelog::{ColorChoice, ConfigBuilder, TermLogger, TerminalMode};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use thiserror::Error;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Converts DDM models to glTF")]
+struct Args {
+    /// Path to input .ddm file or directory to search recursively
+    input: PathBuf,
+    /// Directory to write converted files to, mirroring input directory structure
+    output: PathBuf,
+    /// Wildcard pattern (* and ?) for files in input directory, matched against relative path
+    #[arg(short, long, default_value = "*.ddm")]
+    glob: String,
+    /// Write JSON report of converted files
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Gltf)]
+    format: OutputFormat,
+    /// Skip converting + referencing textures
+    #[arg(long)]
+    no_textures: bool,
+    /// Embed buffer + textures in .gltf as data URIs (always done for glb)
+    #[arg(long)]
+    embed: bool,
+    /// How mesh transforms are exported
+    #[arg(long, value_enum, default_value_t = TransformMode::Trs)]
+    mesh_transforms: TransformMode,
+    /// Replace existing output files
+    #[arg(long)]
+    overwrite: bool,
+    /// Convert without writing any files
+    #[arg(long)]
+    dry_run: bool,
+    /// Log more details (-v: debug, -vv: trace)
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only log errors
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Gltf, // Json + separate .bin + textures unless embedded
+    Glb,  // Single binary file with embedded buffer + textures
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum TransformMode {
+    Trs,    // Node translation, rotation + scale
+    Matrix, // Node matrix
+    Bake,   // Applied to vertex positions + normals
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ConvertOptions {
+    format: OutputFormat,
+    include_textures: bool,
+    embed: bool,
+    transforms: TransformMode,
+}
+
+impl From<&Args> for ConvertOptions {
+    fn from(args: &Args) -> Self {
+        ConvertOptions {
+            format: args.format,
+            include_textures: !args.no_textures,
+            embed: args.embed,
+            transforms: args.mesh_transforms,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+enum ConvertError {
+    #[error("Unable to parse DDM: {0}")]
+    Parse(#[from] OffbeatError),
+    #[error("DDM has {count} error(s) that prevent conversion")]
+    Invalid { count: usize, messages: Vec<String> },
+    #[error("Unable to convert texture \"{path}\": {source}")]
+    Texture { path: PathBuf, source: image::ImageError },
+    #[error("\"{0}\" already exists, use --overwrite to replace it")]
+    OutputExists(PathBuf),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Gltf(#[from] gltf::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// File to write, relative to output dir
+struct OutputFile {
+    name: String,
+    data: Vec<u8>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    init_logger(&args);
 
-fn main() {
-    // Input: [ddm_file_path] [gltf_dir_path]
-    let args: Vec<_> = env::args().skip(1).collect();
+    let jobs = match find_jobs(&args.input, &args.output, &args.glob) {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            error!("Unable to search \"{}\": {err}", args.input.display());
+            return ExitCode::FAILURE;
+        }
+    };
 
-    if args.len() < 2 {
-        println!("ddm2gltf.exe [ddm_file_path] [gltf_dir_path]");
-        return;
+    if jobs.is_empty() {
+        error!("No files in \"{}\" match \"{}\"", args.input.display(), args.glob);
+        return ExitCode::FAILURE;
     }
 
-    let ddm_file_path = Path::new(&args[0]);
-    let mut ddm_file = File::open(ddm_file_path).unwrap();
-    let ddm = DdmFile::from_file(&mut ddm_file).unwrap();
+    // Failed files don't stop the rest
+    let files = jobs
+        .par_iter()
+        .map(|job| convert_file(job, &args))
+        .collect();
 
-    let gltf_output_dir_path = Path::new(&args[1]);
+    let report = BatchReport::new(files);
+    info!(
+        "Converted {} of {} file(s) ({} with warnings, {} failed)",
+        report.succeeded + report.warned,
+        report.total,
+        report.warned,
+        report.failed
+    );
 
-    //println!("{ddm:#?}");
+    if let Some(report_path) = &args.report {
+        let result = serde_json::to_vec_pretty(&report)
+            .map_err(ConvertError::from)
+            .and_then(|data| Ok(std::fs::write(report_path, data)?));
 
-    convert_ddm_to_gltf(ddm_file_path, &ddm, &gltf_output_dir_path);
+        if let Err(err) = result {
+            error!("Unable to write report \"{}\": {err}", report_path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match report.failed {
+        0 => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    }
 }
 
-fn convert_image(dds_path: &Path, png_path: &Path) {
-    use image::{ImageFormat, open};
+fn convert_file(job: &Job, args: &Args) -> FileReport {
+    let mut warnings = Warnings::new(&job.input);
+    let result = run(job, args, &mut warnings);
 
-    // Create dir
-    create_dir_if_not_exists(png_path.parent().unwrap()).unwrap();
+    let (status, outputs, errors) = match result {
+        Ok(outputs) if warnings.messages.is_empty() => (Status::Success, outputs, Vec::new()),
+        Ok(outputs) => (Status::Warning, outputs, Vec::new()),
+        Err(err) => {
+            error!("Unable to convert \"{}\": {err}", job.input.display());
 
-    let image = open(dds_path).unwrap();
-    image.save_with_format(png_path, ImageFormat::Png).unwrap();
+            // Include what made validation fail
+            let mut errors = vec![err.to_string()];
+            if let ConvertError::Invalid { messages, .. } = err {
+                errors.extend(messages);
+            }
+
+            (Status::Error, Vec::new(), errors)
+        }
+    };
+
+    FileReport {
+        input: job.input.to_owned(),
+        status,
+        outputs,
+        warnings: warnings.messages,
+        errors,
+    }
 }
 
-fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
-    let ddm_dir = ddm_path.parent().unwrap();
+fn init_logger(args: &Args) {
+    let level = match (args.quiet, args.verbose) {
+        (true, _) => LevelFilter::Error,
+        (_, 0) => LevelFilter::Info,
+        (_, 1) => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+
+    let config = ConfigBuilder::new()
+        .set_time_level(LevelFilter::Off)
+        .build();
+
+    TermLogger::init(level, config, TerminalMode::Mixed, ColorChoice::Auto).unwrap();
+}
+
+fn run(job: &Job, args: &Args, warnings: &mut Warnings) -> Result<Vec<PathBuf>, ConvertError> {
+    let mut ddm_file = File::open(&job.input)?;
+    let ddm = DdmFile::from_file(&mut ddm_file)?;
+
+    // Check for issues that would break conversion
+    let (errors, issues): (Vec<_>, Vec<_>) = ddm
+        .validate()
+        .into_iter()
+        .partition(|d| d.is_error());
+
+    for message in group_diagnostics(&issues) {
+        warnings.push(message);
+    }
+
+    if !errors.is_empty() {
+        let messages = group_diagnostics(&errors);
+        for message in messages.iter() {
+            error!("\"{}\": {message}", job.input.display());
+        }
+
+        return Err(ConvertError::Invalid { count: errors.len(), messages });
+    }
+
+    trace!("{ddm:#?}");
+
+    let files = convert_ddm_to_gltf(&job.input, &ddm, &ConvertOptions::from(args), warnings)?;
+    write_output_files(&job.output_dir, &files, args.overwrite, args.dry_run)
+}
+
+/// One message per kind of diagnostic, so per-vertex issues don't flood log + report
+fn group_diagnostics(diagnostics: &[DdmDiagnostic]) -> Vec<String> {
+    let mut groups: Vec<(&DdmDiagnostic, usize)> = Vec::new();
+
+    for diag in diagnostics.iter() {
+        match groups.iter_mut().find(|(first, _)| std::mem::discriminant(*first) == std::mem::discriminant(diag)) {
+            Some((_, count)) => *count += 1,
+            None => groups.push((diag, 1)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(first, count)| match count {
+            1 => first.to_string(),
+            _ => format!("{first} (and {} more like it)", count - 1),
+        })
+        .collect()
+}
+
+fn convert_image(dds_path: &Path) -> Result<Vec<u8>, ConvertError> {
+    use image::{ImageOutputFormat, open};
+
+    let to_error = |source| ConvertError::Texture { path: dds_path.to_owned(), source };
+
+    let image = open(dds_path).map_err(to_error)?;
+
+    let mut data = Cursor::new(Vec::new());
+    image.write_to(&mut data, ImageOutputFormat::Png).map_err(to_error)?;
+
+    Ok(data.into_inner())
+}
+
+fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, options: &ConvertOptions, warnings: &mut Warnings) -> Result<Vec<OutputFile>, ConvertError> {
+    let ddm_dir = ddm_path.parent().unwrap_or(Path::new(""));
     let mut acc_builder = AccessorBuilder::new();
 
     let ddm_name = ddm_path
         .file_stem()
-        .and_then(|f| f.to_str())
-        .unwrap();
+        .map(|f| f.to_string_lossy().into_owned())
+        .unwrap_or_default();
 
     // Process textures
-    let texture_names = ddm.meshes
-        .iter()
-        .map(|m| {
-            let tex_name = &m.tex_name;
-            let in_tex_path = ddm_dir.join(format!("{tex_name}.dds"));
-
-            let out_tex_filename = format!("{tex_name}.png");
-            let out_tex_path = output_dir_path.join(&out_tex_filename);
+    let texture_names = match options.include_textures {
+        true => ddm.meshes.iter().map(|m| &m.tex_name).collect::<Vec<_>>(),
+        false => Vec::new(),
+    };
 
-            convert_image(&in_tex_path, &out_tex_path);
-            println!("Wrote \"{out_tex_filename}\"");
+    // Converted png + file name it's written to when not embedded
+    let textures = texture_names
+        .iter()
+        .enumerate()
+        .map(|(i, tex_name)| {
+            let png = convert_image(&ddm_dir.join(format!("{tex_name}.dds")))?;
+            let filename = match safe_relative_path(tex_name) {
+                name if name.is_empty() => format!("{ddm_name}_{i}.png"),
+                name => format!("{name}.png"),
+            };
 
-            tex_name
+            Ok((filename, png))
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, ConvertError>>()?;
+
+    // Build skeleton for skinned models
+    let skeleton = if ddm.kind.is_skinned() && !ddm.bones.is_empty() {
+        match Skeleton::from_ddm(ddm) {
+            Ok(skeleton) => Some(skeleton),
+            Err(err) => {
+                warnings.push(format!("Unable to build skeleton, exporting without skin: {err}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     // Process meshes
     let mut meshes = Vec::new();
+    let mut mesh_nodes = Vec::new(); // Unbaked transform of each mesh + if it's skinned
     let mut materials = Vec::new();
     for (mesh_idx, mesh) in ddm.meshes.iter().enumerate() {
         // Create material
         let mat_index = materials.len() as u32;
         materials.push(json::Material {
-            name: Some(mesh.name.to_owned()),
+            name: Some(mesh.name.to_string()),
             pbr_metallic_roughness: json::material::PbrMetallicRoughness {
-                base_color_texture: Some(json::texture::Info {
+                base_color_texture: options.include_textures.then(|| json::texture::Info {
                         index: json::Index::new(mesh_idx as u32),
                         tex_coord: 0,
                         extensions: None,
@@ -89,37 +336,43 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
 
         let is_single_part = mesh.face_groups.len() <= 1;
 
-        for (i, face_group) in mesh.face_groups.iter().enumerate() {
+        let geometries = ddm.mesh_geometry(mesh_idx, GeometryMode::FaceGroups)?;
+
+        for (i, geometry) in geometries.into_iter().enumerate() {
             let mesh_name = if is_single_part {
-                mesh.name.to_owned()
+                mesh.name.to_string()
             } else {
                 format!("{}.{}", &mesh.name, i)
             };
 
-            // 3 indicies = 1 triangle
-            let index_start = face_group.triangle_start_idx as usize;
-            let index_count = (face_group.triangle_count * 3) as usize;
+            // Skinned primitives need both joints + weights
+            let (bone_idx, weight_idx) = match &skeleton {
+                Some(_) => match add_skin_attributes(&mut acc_builder, ddm, (mesh_idx, i), &mesh_name, &geometry.vertex_ids, warnings) {
+                    (Some(bone_idx), Some(weight_idx)) => (Some(bone_idx), Some(weight_idx)),
+                    _ => (None, None),
+                },
+                None => (None, None),
+            };
+            let is_skinned = bone_idx.is_some();
 
-            let triangle_indicies = &ddm.triangles[index_start..(index_start + index_count)];
+            // Node transforms of skinned meshes are ignored by glTF
+            let transform = mesh.matrix();
+            let bake = transform != Mat4::IDENTITY
+                && (options.transforms == TransformMode::Bake || is_skinned);
 
-            let mut vertices = Vec::new();
-            let mut faces = Vec::new();
-            let mut vert_map = HashMap::new(); // old face idx -> new face idx
+            let baked_vertices: Vec<_> = match bake {
+                true => geometry.vertices.iter().map(|v| v.transformed(&transform)).collect(),
+                false => Vec::new(),
+            };
 
-            // Map verts and faces
-            for old_idx in triangle_indicies {
-                if let Some(new_idx) = vert_map.get(old_idx) {
-                    faces.push(*new_idx);
-                } else {
-                    let vert = &ddm.vertices[*old_idx as usize];
-                    let new_idx = vertices.len() as u16;
+            let vertices: Vec<&DdmVertex> = match bake {
+                true => baked_vertices.iter().collect(),
+                false => geometry.vertices.to_owned(),
+            };
 
-                    vert_map.insert(*old_idx, new_idx);
-                    faces.push(new_idx);
-                    vertices.push(vert);
-                }
-            }
+            mesh_nodes.push(((!bake).then_some(transform), is_skinned));
 
+            // Mirror x axis to convert to right-handed
             let pos_idx = acc_builder.add_array(
                 format!("{}_pos", &mesh_name),
                 vertices.iter().map(|v| [-v.x, v.y, v.z])
@@ -127,7 +380,7 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
 
             let norm_idx = acc_builder.add_array(
                 format!("{}_norm", &mesh_name),
-                vertices.iter().map(|v| [v.nx, v.ny, v.nz])
+                vertices.iter().map(|v| [-v.nx, v.ny, v.nz])
             );
 
             let uv_idx = acc_builder.add_array(
@@ -138,7 +391,7 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
             // Need to be scalar for some reason
             let face_idx = acc_builder.add_scalar(
                 format!("{}_face", &mesh_name),
-                faces
+                geometry.indices
             );
 
             meshes.push(json::Mesh {
@@ -173,7 +426,7 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
                             }
 
                             // Add weights
-                            /*if let Some(acc_idx) = weight_idx {
+                            if let Some(acc_idx) = weight_idx {
                                 map.insert(
                                     json::validation::Checked::Valid(json::mesh::Semantic::Weights(0)),
                                     json::Index::new(acc_idx as u32)
@@ -189,7 +442,7 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
                             }
 
                             // Add tangents
-                            if let Some(acc_idx) = tan_idx {
+                            /*if let Some(acc_idx) = tan_idx {
                                 map.insert(
                                     json::validation::Checked::Valid(json::mesh::Semantic::Tangents),
                                     json::Index::new(acc_idx as u32)
@@ -214,6 +467,14 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
         }
     }
 
+    // Joint nodes are added after mesh nodes
+    let joint_node_start = 1 + meshes.len();
+
+    let inverse_bind_idx = skeleton.as_ref().and_then(|skeleton| acc_builder.add_array(
+        format!("{ddm_name}_inverse_bind"),
+        skeleton.joints.iter().map(|j| j.inverse_bind.to_right_handed().0)
+    ));
+
     // Create gltf json
     let mut gltf = json::Root {
         asset: json::Asset {
@@ -235,7 +496,7 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
                 buffer_view: None,
                 mime_type: Some(json::image::MimeType(String::from("image/png"))),
                 name: Some(tex_name.to_string()),
-                uri: Some(format!("{tex_name}.png")),
+                uri: None, // Set when packing files
                 extensions: None,
                 extras: None
             })
@@ -258,8 +519,11 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
             nodes.push(json::Node {
                 camera: None,
                 children: Some((0..meshes.len())
-                    .into_iter()
                     .map(|i| json::Index::new((i + 1) as u32))
+                    .chain(skeleton
+                        .iter()
+                        .flat_map(|s| s.roots.iter())
+                        .map(|r| json::Index::new((joint_node_start + r) as u32)))
                     .collect()),
                 extensions: None,
                 extras: None,
@@ -274,18 +538,58 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
             });
 
             // Mesh nodes
-            for i in 0..meshes.len() {
+            for (i, (transform, is_skinned)) in mesh_nodes.iter().enumerate() {
+                // Same handedness conversion as vertices
+                let transform = transform
+                    .filter(|m| *m != Mat4::IDENTITY)
+                    .map(|m| m.to_right_handed());
+
+                let (matrix, trs) = match (transform, options.transforms) {
+                    (Some(m), TransformMode::Matrix) => (Some(m.0), None),
+                    (Some(m), _) => match m.try_decompose() {
+                        Some(trs) => (None, Some(trs)),
+                        None => {
+                            warnings.push(format!("Transform of \"{}\" has shear, exporting as matrix", meshes[i].name.as_deref().unwrap_or_default()));
+                            (Some(m.0), None)
+                        },
+                    },
+                    (None, _) => (None, None),
+                };
+
                 nodes.push(json::Node {
                     camera: None,
                     children: None,
                     extensions: None,
                     extras: None,
-                    matrix: None,
+                    matrix,
                     mesh: Some(json::Index::new(i as u32)),
                     name: None,
-                    rotation: None,
-                    scale: None,
-                    translation: None,
+                    rotation: trs.map(|t| json::scene::UnitQuaternion(t.rotation)),
+                    scale: trs.map(|t| t.scale),
+                    translation: trs.map(|t| t.translation),
+                    skin: is_skinned.then(|| json::Index::new(0)),
+                    weights: None,
+                });
+            }
+
+            // Joint nodes
+            for joint in skeleton.iter().flat_map(|s| s.joints.iter()) {
+                let trs = joint.local.to_right_handed().decompose();
+
+                nodes.push(json::Node {
+                    camera: None,
+                    children: (!joint.children.is_empty()).then(|| joint.children
+                        .iter()
+                        .map(|c| json::Index::new((joint_node_start + c) as u32))
+                        .collect()),
+                    extensions: None,
+                    extras: None,
+                    matrix: None,
+                    mesh: None,
+                    name: Some(joint.name.to_owned()),
+                    rotation: Some(json::scene::UnitQuaternion(trs.rotation)),
+                    scale: Some(trs.scale),
+                    translation: Some(trs.translation),
                     skin: None,
                     weights: None,
                 });
@@ -293,6 +597,19 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
 
             nodes
         },
+        skins: skeleton
+            .iter()
+            .map(|s| json::Skin {
+                extensions: None,
+                extras: None,
+                inverse_bind_matrices: inverse_bind_idx.map(|idx| json::Index::new(idx as u32)),
+                joints: (0..s.joints.len())
+                    .map(|i| json::Index::new((joint_node_start + i) as u32))
+                    .collect(),
+                name: Some(ddm_name.to_string()),
+                skeleton: None,
+            })
+            .collect(),
         scenes: vec![
             json::Scene {
                 name: None,
@@ -314,16 +631,98 @@ fn convert_ddm_to_gltf(ddm_path: &Path, ddm: &DdmFile, output_dir_path: &Path) {
         ..Default::default()
     };
 
-    // Write files
-    build_binary(ddm_name, output_dir_path, &mut gltf, acc_builder);
+    pack_files(&ddm_name, &mut gltf, acc_builder, textures, options)
+}
+
+fn add_skin_attributes(acc_builder: &mut AccessorBuilder, ddm: &DdmFile, (mesh_idx, group_idx): (usize, usize), mesh_name: &str, vertex_ids: &[u16], warnings: &mut Warnings) -> (Option<usize>, Option<usize>) {
+    let resolved = match ddm.resolve_palette(mesh_idx, group_idx, BoneMapping::Position) {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            warnings.push(format!("Unable to resolve bones of \"{mesh_name}\": {err}"));
+            return (None, None);
+        }
+    };
+
+    if !resolved.issues.is_empty() {
+        warnings.push(format!("{} bone influences of \"{mesh_name}\" couldn't be resolved", resolved.issues.len()));
+    }
+
+    // Palette is already remapped to bone indices, which match joint order
+    let influences: Vec<_> = vertex_ids
+        .iter()
+        .map(|id| {
+            let mut influence = resolved.influences.get(id).copied().unwrap_or_default();
+            let sum: f32 = influence.weights.iter().sum();
+
+            if sum > 0.0 {
+                influence.weights = influence.weights.map(|w| w / sum);
+            } else {
+                // Weights must sum to 1 so fall back to first joint
+                influence = VertexInfluence { bones: [0; 4], weights: [1.0, 0.0, 0.0, 0.0] };
+            }
+
+            influence
+        })
+        .collect();
+
+    let bone_idx = acc_builder.add_array(
+        format!("{mesh_name}_bones"),
+        influences.iter().map(|i| i.bones)
+    );
+
+    let weight_idx = acc_builder.add_array(
+        format!("{mesh_name}_weights"),
+        influences.iter().map(|i| i.weights)
+    );
+
+    (bone_idx, weight_idx)
+}
+
+fn write_output_files(output_dir: &Path, files: &[OutputFile], overwrite: bool, dry_run: bool) -> Result<Vec<PathBuf>, ConvertError> {
+    let paths: Vec<_> = files
+        .iter()
+        .map(|f| output_dir.join(&f.name))
+        .collect();
+
+    // Only waits on jobs writing same paths
+    let _claim = OutputClaim::new(&paths);
+
+    // Files with identical data aren't conflicts
+    let is_unchanged = |file: &OutputFile, path: &Path| std::fs::read(path)
+        .is_ok_and(|data| data == file.data);
+
+    if !overwrite {
+        let existing = files
+            .iter()
+            .zip(paths.iter())
+            .find(|(f, p)| p.exists() && !is_unchanged(f, p));
+
+        if let Some((_, path)) = existing {
+            return Err(ConvertError::OutputExists(path.to_owned()));
+        }
+    }
+
+    if dry_run {
+        for (file, path) in files.iter().zip(paths.iter()) {
+            info!("Would write \"{}\" ({} bytes)", path.display(), file.data.len());
+        }
+
+        return Ok(paths);
+    }
+
+    for (file, path) in files.iter().zip(paths.iter()) {
+        if !overwrite && is_unchanged(file, path) {
+            debug!("Skipped unchanged \"{}\"", path.display());
+            continue;
+        }
 
-    // Write gltf json
-    let gltf_filename = format!("{ddm_name}.gltf");
-    let gltf_path = output_dir_path.join(&gltf_filename);
-    let writer = std::fs::File::create(&gltf_path).expect("I/O error");
-    json::serialize::to_writer_pretty(writer, &gltf).expect("Serialization error");
+        // Textures can be in sub directories
+        create_dir_if_not_exists(path.parent().unwrap_or(output_dir))?;
+        std::fs::write(path, &file.data)?;
+        info!("Wrote \"{}\"", path.display());
+    }
 
-    println!("Wrote \"{gltf_filename}\"");
+    Ok(paths)
 }
 
 fn create_dir_if_not_exists<T>(dir_path: T) -> Result<(), std::io::Error> where T: AsRef<Path> {
@@ -331,38 +730,465 @@ fn create_dir_if_not_exists<T>(dir_path: T) -> Result<(), std::io::Error> where
 
     if !dir_path.exists() {
         // Not found, create directory
-        std::fs::create_dir_all(&dir_path)?;
+        std::fs::create_dir_all(dir_path)?;
     }
 
     Ok(())
 }
 
-fn build_binary(basename: &str, output_dir: &Path, gltf: &mut json::Root, acc_builder: AccessorBuilder) {
-    // Write as external file
-    create_dir_if_not_exists(output_dir).unwrap();
+fn pack_files(basename: &str, gltf: &mut json::Root, acc_builder: AccessorBuilder, textures: Vec<(String, Vec<u8>)>, options: &ConvertOptions) -> Result<Vec<OutputFile>, ConvertError> {
+    let bin_filename = format!("{basename}.bin");
+    let (accessors, views, mut buffer, mut data) = acc_builder.generate(&bin_filename);
 
-    //let basename = self.get_basename();
-    let filename = format!("{basename}.bin");
-    let bin_path = output_dir.join(&filename);
+    gltf.accessors = accessors;
+    gltf.buffer_views = views;
 
-    let (accessors, views, buffer, data) = acc_builder.generate(&filename);
+    let mut files = Vec::new();
 
-    let mut writer = std::fs::File::create(&bin_path).unwrap();
-    writer.write_all(&data).unwrap();
+    match (options.format, options.embed) {
+        (OutputFormat::Glb, _) => {
+            // Images are stored as buffer views after geometry
+            for (image, (_, png)) in gltf.images.iter_mut().zip(textures) {
+                data.resize(data.len().next_multiple_of(4), 0);
 
-    println!("Wrote \"{filename}\"");
+                image.buffer_view = Some(json::Index::new(gltf.buffer_views.len() as u32));
+                gltf.buffer_views.push(json::buffer::View {
+                    buffer: json::Index::new(0),
+                    byte_length: png.len().into(),
+                    byte_offset: Some(data.len().into()),
+                    byte_stride: None,
+                    name: image.name.clone(),
+                    target: None,
+                    extensions: None,
+                    extras: None,
+                });
 
-    /*buffer.uri = {
-        use base64::{Engine as _, engine::{self, general_purpose}, alphabet};
+                data.extend(png);
+            }
 
-        let mut str_data = String::from("data:application/octet-stream;base64,");
-        general_purpose::STANDARD.encode_string(&data, &mut str_data);
+            buffer.byte_length = data.len().into();
+            buffer.uri = None;
+            gltf.buffers = vec![buffer];
 
-        Some(str_data)
-    };*/
+            let glb = gltf::binary::Glb {
+                header: gltf::binary::Header {
+                    magic: *b"glTF",
+                    version: 2,
+                    length: 0, // Calculated when written
+                },
+                json: Cow::Owned(json::serialize::to_vec(gltf)?),
+                bin: Some(Cow::Owned(data)),
+            };
 
-    // Update bin data
-    gltf.accessors = accessors;
-    gltf.buffers = vec![buffer];
-    gltf.buffer_views = views;
+            files.push(OutputFile {
+                name: format!("{basename}.glb"),
+                data: glb.to_vec()?,
+            });
+        },
+        (OutputFormat::Gltf, true) => {
+            for (image, (_, png)) in gltf.images.iter_mut().zip(textures) {
+                image.uri = Some(to_data_uri("image/png", &png));
+            }
+
+            buffer.uri = Some(to_data_uri("application/octet-stream", &data));
+            gltf.buffers = vec![buffer];
+
+            files.push(OutputFile {
+                name: format!("{basename}.gltf"),
+                data: json::serialize::to_vec_pretty(gltf)?,
+            });
+        },
+        (OutputFormat::Gltf, false) => {
+            for (image, (filename, png)) in gltf.images.iter_mut().zip(textures) {
+                image.uri = Some(filename.to_owned());
+
+                files.push(OutputFile {
+                    name: filename,
+                    data: png,
+                });
+            }
+
+            // Write as external file
+            buffer.uri = Some(bin_filename.to_owned());
+            gltf.buffers = vec![buffer];
+
+            files.push(OutputFile {
+                name: bin_filename,
+                data,
+            });
+
+            files.push(OutputFile {
+                name: format!("{basename}.gltf"),
+                data: json::serialize::to_vec_pretty(gltf)?,
+            });
+        },
+    }
+
+    Ok(files)
+}
+
+/// Reduces name to path that stays inside output dir (no root, `.` or `..` components)
+fn safe_relative_path(name: &str) -> String {
+    use std::path::Component;
+
+    let name = name.replace('\\', "/");
+
+    Path::new(&name)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn to_data_uri(mime_type: &str, data: &[u8]) -> String {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let mut uri = format!("data:{mime_type};base64,");
+    general_purpose::STANDARD.encode_string(data, &mut uri);
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Single triangle, skinned to 2 bones through palette of [1, 0]
+    fn create_test_ddm(kind: DdmKind, translation: [f32; 3]) -> DdmFile {
+        let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+        DdmFile {
+            kind,
+            meshes: vec![DdmMesh {
+                name: "body".into(),
+                transform: Mat4::from_translation(translation).0,
+                tex_name: "body_tex".into(),
+                tex_ext: "dds".into(),
+                face_groups: vec![DdmFaceGroup {
+                    index_count: 2,
+                    indicies: std::array::from_fn(|i| if i == 0 { 1 } else { 0 }),
+                    triangle_start_idx: 0,
+                    triangle_count: 1,
+                }],
+                ..Default::default()
+            }],
+            bones: match kind.is_skinned() {
+                true => (0..2)
+                    .map(|i| DdmBone {
+                        transform: Mat4::from_translation([0.0, i as f32, 0.0]).0,
+                        name: format!("bone_{i}").into(),
+                        id: i,
+                    })
+                    .collect(),
+                false => Vec::new(),
+            },
+            triangles: vec![0, 1, 2],
+            vertices: positions
+                .iter()
+                .enumerate()
+                .map(|(i, [x, y, z])| DdmVertex {
+                    x: *x,
+                    y: *y,
+                    z: *z,
+                    nx: 1.0,
+                    skin: kind.is_skinned().then_some(DdmSkin {
+                        bones: [(i % 2) as f32, 0.0, 0.0, 0.0],
+                        weights: [1.0, 0.0, 0.0, 0.0],
+                    }),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn convert_with_textures(input: &Path, ddm: &DdmFile, args: &[&str]) -> Vec<OutputFile> {
+        let args = Args::try_parse_from(["ddm2gltf", input.to_str().unwrap(), "out"].iter().chain(args)).unwrap();
+        let mut warnings = Warnings::new(&args.input);
+
+        convert_ddm_to_gltf(&args.input, ddm, &ConvertOptions::from(&args), &mut warnings).unwrap()
+    }
+
+    fn convert(ddm: &DdmFile, args: &[&str]) -> Vec<OutputFile> {
+        let args = [&["--no-textures"], args].concat();
+        convert_with_textures(Path::new("model.ddm"), ddm, &args)
+    }
+
+    // Single 4x4 DXT1 block
+    fn write_test_dds(path: &Path) {
+        let mut data = vec![0u8; 128];
+        data[..4].copy_from_slice(b"DDS ");
+        for (offset, value) in [(4, 124u32), (8, 0x81007), (12, 4), (16, 4), (20, 8), (76, 32), (80, 0x4), (108, 0x1000)] {
+            data[offset..(offset + 4)].copy_from_slice(&value.to_le_bytes());
+        }
+        data[84..88].copy_from_slice(b"DXT1");
+        data.extend([0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0]);
+
+        std::fs::write(path, data).unwrap();
+    }
+
+    // Returns json + binary chunk of glb
+    fn read_glb(data: &[u8]) -> (json::Root, Vec<u8>) {
+        let glb = gltf::Glb::from_slice(data).unwrap();
+        let root = serde_json::from_slice(&glb.json).unwrap();
+
+        (root, glb.bin.unwrap_or_default().into_owned())
+    }
+
+    fn accessor_data<'a>(root: &json::Root, bin: &'a [u8], idx: u32) -> &'a [u8] {
+        let accessor = &root.accessors[idx as usize];
+        let view = &root.buffer_views[accessor.buffer_view.unwrap().value()];
+        let start = view.byte_offset.map(|o| o.0 as usize).unwrap_or_default();
+
+        &bin[start..(start + view.byte_length.0 as usize)]
+    }
+
+    fn read_vec3(root: &json::Root, bin: &[u8], semantic: json::mesh::Semantic) -> Vec<[f32; 3]> {
+        let idx = root.meshes[0].primitives[0].attributes[&json::validation::Checked::Valid(semantic)];
+
+        accessor_data(root, bin, idx.value() as u32)
+            .chunks_exact(12)
+            .map(|c| std::array::from_fn(|i| f32::from_le_bytes(c[(i * 4)..(i * 4 + 4)].try_into().unwrap())))
+            .collect()
+    }
+
+    #[test]
+    fn glb_format_test() {
+        let ddm = create_test_ddm(DdmKind::Static, [0.0; 3]);
+        let files = convert(&ddm, &["--format", "glb"]);
+
+        assert_eq!(vec!["model.glb"], files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>());
+
+        let (root, bin) = read_glb(&files[0].data);
+        assert_eq!(1, root.buffers.len());
+        assert_eq!(None, root.buffers[0].uri);
+        assert_eq!(bin.len(), (root.buffers[0].byte_length.0 as usize).next_multiple_of(4)); // Chunk is padded
+        assert_eq!(1, root.meshes.len());
+    }
+
+    #[test]
+    fn skinned_test() {
+        use json::accessor::{ComponentType, GenericComponentType, Type};
+        use json::validation::Checked::Valid;
+
+        let ddm = create_test_ddm(DdmKind::Skinned, [0.0; 3]);
+        let files = convert(&ddm, &["--format", "glb"]);
+        let (root, bin) = read_glb(&files[0].data);
+
+        assert_eq!(1, root.skins.len());
+        let skin = &root.skins[0];
+        assert_eq!(ddm.bones.len(), skin.joints.len());
+
+        let inverse_bind = &root.accessors[skin.inverse_bind_matrices.unwrap().value()];
+        assert_eq!(Valid(Type::Mat4), inverse_bind.type_);
+        assert!(matches!(inverse_bind.component_type, Valid(GenericComponentType(ComponentType::F32))));
+        assert_eq!(skin.joints.len(), inverse_bind.count.0 as usize);
+
+        let primitive = &root.meshes[0].primitives[0];
+        let joints_idx = primitive.attributes[&Valid(json::mesh::Semantic::Joints(0))];
+        let joints = &root.accessors[joints_idx.value()];
+        assert_eq!(Valid(Type::Vec4), joints.type_);
+        assert!(matches!(joints.component_type, Valid(GenericComponentType(ComponentType::U16))));
+
+        // Palette maps vertex bones to [1, 0]
+        let indices: Vec<_> = accessor_data(&root, &bin, joints_idx.value() as u32)
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(4 * ddm.vertices.len(), indices.len());
+        assert!(indices.iter().all(|i| (*i as usize) < skin.joints.len()));
+        assert_eq!(vec![1, 0, 1], indices.iter().step_by(4).copied().collect::<Vec<_>>());
+
+        assert!(root.nodes.iter().filter(|n| n.mesh.is_some()).all(|n| n.skin == Some(json::Index::new(0))));
+    }
+
+    #[test]
+    fn mesh_transforms_test() {
+        let ddm = create_test_ddm(DdmKind::Static, [1.0, 2.0, 3.0]);
+        let unbaked = vec![[0.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+        // Translation is mirrored like positions
+        let (root, bin) = read_glb(&convert(&ddm, &["--format", "glb", "--mesh-transforms", "trs"])[0].data);
+        let node = root.nodes.iter().find(|n| n.mesh.is_some()).unwrap();
+        assert_eq!(Some([-1.0, 2.0, 3.0]), node.translation);
+        assert_eq!(None, node.matrix);
+        assert_eq!(unbaked, read_vec3(&root, &bin, json::mesh::Semantic::Positions));
+        assert_eq!(vec![[-1.0, 0.0, 0.0]; 3], read_vec3(&root, &bin, json::mesh::Semantic::Normals)); // Mirrored like positions
+
+        let (root, bin) = read_glb(&convert(&ddm, &["--format", "glb", "--mesh-transforms", "matrix"])[0].data);
+        let node = root.nodes.iter().find(|n| n.mesh.is_some()).unwrap();
+        assert_eq!(Some(Mat4::from_translation([-1.0, 2.0, 3.0]).0), node.matrix);
+        assert_eq!(None, node.translation);
+        assert_eq!(unbaked, read_vec3(&root, &bin, json::mesh::Semantic::Positions));
+    }
+
+    #[test]
+    fn mesh_transforms_bake_test() {
+        let mut ddm = create_test_ddm(DdmKind::Static, [1.0, 2.0, 3.0]);
+        ddm.meshes[0].transform[..8].copy_from_slice(&[0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0]); // 90 degrees around z
+
+        let (root, bin) = read_glb(&convert(&ddm, &["--format", "glb", "--mesh-transforms", "bake"])[0].data);
+        let node = root.nodes.iter().find(|n| n.mesh.is_some()).unwrap();
+        assert_eq!((None, None, None), (node.matrix, node.translation, node.rotation.as_ref().map(|r| r.0)));
+
+        assert_eq!(vec![[-1.0, 2.0, 3.0], [-1.0, 3.0, 3.0], [0.0, 2.0, 3.0]], read_vec3(&root, &bin, json::mesh::Semantic::Positions));
+        assert_eq!(vec![[0.0, 1.0, 0.0]; 3], read_vec3(&root, &bin, json::mesh::Semantic::Normals));
+    }
+
+    #[test]
+    fn mesh_transforms_skinned_bake_test() {
+        // Node transforms of skinned meshes are ignored so always baked
+        let ddm = create_test_ddm(DdmKind::Skinned, [1.0, 2.0, 3.0]);
+
+        let (root, bin) = read_glb(&convert(&ddm, &["--format", "glb", "--mesh-transforms", "trs"])[0].data);
+        let node = root.nodes.iter().find(|n| n.mesh.is_some()).unwrap();
+        assert_eq!((None, None), (node.matrix, node.translation));
+        assert!(node.skin.is_some());
+
+        assert_eq!(vec![[-1.0, 2.0, 3.0], [-2.0, 2.0, 3.0], [-1.0, 3.0, 3.0]], read_vec3(&root, &bin, json::mesh::Semantic::Positions));
+        assert_eq!(vec![[-1.0, 0.0, 0.0]; 3], read_vec3(&root, &bin, json::mesh::Semantic::Normals));
+    }
+
+    #[test]
+    fn texture_packing_test() {
+        const PNG_MAGIC: &[u8] = b"\x89PNG";
+
+        let dir = std::env::temp_dir().join(format!("ddm2gltf_{}_textures", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_test_dds(&dir.join("body_tex.dds"));
+
+        let input = dir.join("model.ddm");
+        let ddm = create_test_ddm(DdmKind::Static, [0.0; 3]);
+
+        // Images are stored as buffer views of glb
+        let files = convert_with_textures(&input, &ddm, &["--format", "glb"]);
+        assert_eq!(1, files.len());
+
+        let (root, bin) = read_glb(&files[0].data);
+        assert_eq!(1, root.images.len());
+        assert_eq!(None, root.images[0].uri);
+
+        let view = &root.buffer_views[root.images[0].buffer_view.unwrap().value()];
+        let start = view.byte_offset.unwrap().0 as usize;
+        assert_eq!(0, start % 4);
+        assert!(start + view.byte_length.0 as usize <= bin.len());
+        assert!(bin[start..].starts_with(PNG_MAGIC));
+
+        // Embedded as data uris
+        let files = convert_with_textures(&input, &ddm, &["--embed"]);
+        let root: json::Root = serde_json::from_slice(&files[0].data).unwrap();
+        assert_eq!(vec!["model.gltf"], files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>());
+        assert!(root.images[0].uri.as_ref().unwrap().starts_with("data:image/png;base64,"));
+
+        // External files are named after texture
+        let files = convert_with_textures(&input, &ddm, &[]);
+        assert_eq!(vec!["body_tex.png", "model.bin", "model.gltf"], files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>());
+        assert!(files[0].data.starts_with(PNG_MAGIC));
+
+        let root: json::Root = serde_json::from_slice(&files[2].data).unwrap();
+        assert_eq!(Some("body_tex.png"), root.images[0].uri.as_deref());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn safe_relative_path_test() {
+        assert_eq!("textures/body", safe_relative_path("textures/body"));
+        assert_eq!("textures/body", safe_relative_path("textures\\body"));
+        assert_eq!("evil", safe_relative_path("../../evil"));
+        assert_eq!("abs/tex", safe_relative_path("/abs/./tex"));
+        assert_eq!("", safe_relative_path(".."));
+    }
+
+    #[test]
+    fn texture_sub_dir_test() {
+        let dir = std::env::temp_dir().join(format!("ddm2gltf_{}_texture_dirs", std::process::id()));
+        std::fs::create_dir_all(dir.join("in/textures")).unwrap();
+        write_test_dds(&dir.join("in/textures/body_tex.dds"));
+
+        let mut ddm = create_test_ddm(DdmKind::Static, [0.0; 3]);
+        ddm.meshes[0].tex_name.set("textures/body_tex");
+
+        let files = convert_with_textures(&dir.join("in/model.ddm"), &ddm, &[]);
+        assert_eq!("textures/body_tex.png", files[0].name);
+
+        // Parent dirs are created for nested textures
+        let output_dir = dir.join("out");
+        write_output_files(&output_dir, &files, false, false).unwrap();
+        assert!(output_dir.join("textures/body_tex.png").is_file());
+
+        // Traversal is stripped so files stay in output dir
+        let outside_name = format!("ddm2gltf_{}_outside", std::process::id());
+        ddm.meshes[0].tex_name.set(format!("../../{outside_name}"));
+        write_test_dds(&dir.join(format!("../{outside_name}.dds")));
+        let files = convert_with_textures(&dir.join("in/model.ddm"), &ddm, &[]);
+        assert_eq!(format!("{outside_name}.png"), files[0].name);
+
+        std::fs::remove_file(dir.join(format!("../{outside_name}.dds"))).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skinned_fallback_test() {
+        let joints = json::validation::Checked::Valid(json::mesh::Semantic::Joints(0));
+
+        // Group without triangles has no joints or weights to write
+        let mut ddm = create_test_ddm(DdmKind::Skinned, [0.0; 3]);
+        let mut empty_mesh = ddm.meshes[0].clone();
+        empty_mesh.face_groups[0].triangle_count = 0;
+        ddm.meshes.push(empty_mesh);
+
+        let (root, _) = read_glb(&convert(&ddm, &["--format", "glb"])[0].data);
+        let skins: Vec<_> = root.nodes
+            .iter()
+            .filter_map(|n| n.mesh.map(|m| (root.meshes[m.value()].primitives[0].attributes.contains_key(&joints), n.skin.is_some())))
+            .collect();
+        assert_eq!(vec![(true, true), (false, false)], skins);
+
+        // Singular bone exports without skin
+        let mut ddm = create_test_ddm(DdmKind::Skinned, [0.0; 3]);
+        ddm.bones[1].transform = [0.0; 16];
+
+        let mut warnings = Warnings::new(Path::new("model.ddm"));
+        let options = ConvertOptions { format: OutputFormat::Glb, include_textures: false, embed: false, transforms: TransformMode::Trs };
+        let files = convert_ddm_to_gltf(Path::new("model.ddm"), &ddm, &options, &mut warnings).unwrap();
+        assert_eq!(1, warnings.messages.len());
+
+        let (root, _) = read_glb(&files[0].data);
+        assert!(root.skins.is_empty());
+        assert!(root.nodes.iter().all(|n| n.skin.is_none()));
+        assert!(!root.meshes[0].primitives[0].attributes.contains_key(&joints));
+    }
+
+    #[test]
+    fn group_diagnostics_test() {
+        let diagnostics: Vec<_> = (0..1000)
+            .map(|vertex| DdmDiagnostic::NonFinitePosition { vertex })
+            .chain([DdmDiagnostic::DuplicateBoneId { bone: 1, first_bone: 0, id: 5 }])
+            .collect();
+
+        assert_eq!(vec![
+            String::from("Vertex 0 has non-finite position (and 999 more like it)"),
+            String::from("Bone 1 has id 5 already used by bone 0"),
+        ], group_diagnostics(&diagnostics));
+    }
+
+    #[test]
+    fn mesh_transforms_shear_test() {
+        let mut ddm = create_test_ddm(DdmKind::Static, [1.0, 2.0, 3.0]);
+        ddm.meshes[0].transform[4] = 0.5; // Y axis leans into X
+
+        let mut warnings = Warnings::new(Path::new("model.ddm"));
+        let options = ConvertOptions { format: OutputFormat::Glb, include_textures: false, embed: false, transforms: TransformMode::Trs };
+        let files = convert_ddm_to_gltf(Path::new("model.ddm"), &ddm, &options, &mut warnings).unwrap();
+        assert_eq!(1, warnings.messages.len());
+
+        // Falls back to matrix since TRS can't represent shear
+        let (root, _) = read_glb(&files[0].data);
+        let node = root.nodes.iter().find(|n| n.mesh.is_some()).unwrap();
+        assert_eq!(Some(ddm.meshes[0].matrix().to_right_handed().0), node.matrix);
+        assert_eq!((None, None), (node.translation, node.scale));
+    }
 }
\ No newline at end of file
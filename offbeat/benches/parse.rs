@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use offbeat::*;
+use std::io::Cursor;
+
+const VERTEX_COUNT: usize = 0xFFFF;
+const TRIANGLE_COUNT: usize = 0x20000;
+
+fn create_large_skinned_ddm() -> Vec<u8> {
+    let ddm = DdmFile {
+        kind: DdmKind::Skinned,
+        meshes: (0..8)
+            .map(|i| DdmMesh {
+                name: format!("mesh_{i}").into(),
+                tex_name: format!("tex_{i}").into(),
+                tex_ext: "dds".into(),
+                face_groups: vec![Default::default(); 4],
+                ..Default::default()
+            })
+            .collect(),
+        bones: (0..100)
+            .map(|i| DdmBone { name: format!("bone_{i}").into(), id: i, ..Default::default() })
+            .collect(),
+        triangles: (0..(TRIANGLE_COUNT * 3))
+            .map(|i| (i % VERTEX_COUNT) as u16)
+            .collect(),
+        vertices: (0..VERTEX_COUNT)
+            .map(|i| DdmVertex {
+                x: i as f32,
+                y: -(i as f32),
+                nz: 1.0,
+                skin: Some(DdmSkin {
+                    bones: [(i % 30) as f32, 0.0, 0.0, 0.0],
+                    weights: [1.0, 0.0, 0.0, 0.0],
+                }),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut stream = Cursor::new(Vec::new());
+    ddm.write_to(&mut stream).unwrap();
+    stream.into_inner()
+}
+
+// Decodes vertex buffer one field at a time for comparison
+fn read_vertices_per_field(data: &[u8]) -> Vec<DdmVertex> {
+    let mut stream = Cursor::new(data);
+    let mut reader = ByteReader::new(&mut stream);
+
+    (0..(data.len() / 64))
+        .map(|_| DdmVertex {
+            x: reader.read().unwrap(),
+            y: reader.read().unwrap(),
+            z: reader.read().unwrap(),
+            nx: reader.read().unwrap(),
+            ny: reader.read().unwrap(),
+            nz: reader.read().unwrap(),
+            u: reader.read().unwrap(),
+            v: reader.read().unwrap(),
+            skin: Some(DdmSkin {
+                bones: [reader.read().unwrap(), reader.read().unwrap(), reader.read().unwrap(), reader.read().unwrap()],
+                weights: [reader.read().unwrap(), reader.read().unwrap(), reader.read().unwrap(), reader.read().unwrap()],
+            }),
+        })
+        .collect()
+}
+
+fn parse_benchmark(c: &mut Criterion) {
+    let data = create_large_skinned_ddm();
+    let vertex_data = DdmFileRef::from_bytes(&data).unwrap().vertex_data();
+
+    let mut group = c.benchmark_group("large_skinned");
+
+    group.bench_function("vertices_per_field", |b| b.iter(|| read_vertices_per_field(black_box(vertex_data))));
+    group.bench_function("from_file", |b| b.iter(|| DdmFile::from_file(&mut Cursor::new(black_box(&data))).unwrap()));
+    group.bench_function("from_bytes", |b| b.iter(|| DdmFile::from_bytes(black_box(&data)).unwrap()));
+    group.bench_function("ref_from_bytes", |b| b.iter(|| DdmFileRef::from_bytes(black_box(&data)).unwrap()));
+    group.bench_function("ref_from_bytes_iter", |b| b.iter(|| {
+        DdmFileRef::from_bytes(black_box(&data))
+            .unwrap()
+            .vertices()
+            .fold(0.0f32, |acc, v| acc + v.x)
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, parse_benchmark);
+criterion_main!(benches);
\ No newline at end of file
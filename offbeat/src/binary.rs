@@ -0,0 +1,113 @@
+use crate::{ByteReader, ByteWriter, OffbeatError, Primitive};
+use std::io::{Read, Seek, Write};
+
+/// Structure that can be read from stream, usually derived with `#[derive(BinRead)]`
+pub trait BinRead<C = ()> : Sized {
+    fn bin_read<T: Read + Seek>(reader: &mut ByteReader<T>, ctx: &C) -> Result<Self, OffbeatError>;
+}
+
+/// Structure that can be written to stream, usually derived with `#[derive(BinWrite)]`
+pub trait BinWrite<C = ()> {
+    fn bin_write<T: Write + Seek>(&self, writer: &mut ByteWriter<T>, ctx: &C) -> Result<(), OffbeatError>;
+}
+
+impl<C, P: Primitive> BinRead<C> for P {
+    fn bin_read<T: Read + Seek>(reader: &mut ByteReader<T>, _ctx: &C) -> Result<Self, OffbeatError> {
+        Ok(reader.read()?)
+    }
+}
+
+impl<C, P: Primitive> BinWrite<C> for P {
+    fn bin_write<T: Write + Seek>(&self, writer: &mut ByteWriter<T>, _ctx: &C) -> Result<(), OffbeatError> {
+        Ok(writer.write(self)?)
+    }
+}
+
+// Optional blocks are usually paired with `cond`, missing values are written as default
+impl<C, B: BinRead<C>> BinRead<C> for Option<B> {
+    fn bin_read<T: Read + Seek>(reader: &mut ByteReader<T>, ctx: &C) -> Result<Self, OffbeatError> {
+        B::bin_read(reader, ctx).map(Some)
+    }
+}
+
+impl<C, B: BinWrite<C> + Default> BinWrite<C> for Option<B> {
+    fn bin_write<T: Write + Seek>(&self, writer: &mut ByteWriter<T>, ctx: &C) -> Result<(), OffbeatError> {
+        match self {
+            Some(value) => value.bin_write(writer, ctx),
+            None => B::default().bin_write(writer, ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinRead, BinWrite, RawString};
+    use std::io::Cursor;
+
+    struct TestContext {
+        has_extra: bool,
+    }
+
+    #[derive(Debug, Default, PartialEq, BinRead, BinWrite)]
+    #[offbeat(ctx = TestContext)]
+    struct TestItem {
+        id: u16,
+        #[offbeat(cond = "ctx.has_extra")]
+        extra: u16,
+    }
+
+    #[derive(Debug, Default, PartialEq, BinRead, BinWrite)]
+    #[offbeat(ctx = TestContext)]
+    struct TestStruct {
+        #[offbeat(string = 8)]
+        name: RawString,
+        #[offbeat(pad = 4)]
+        values: [f32; 2],
+        #[offbeat(count = u32)]
+        items: Vec<TestItem>,
+    }
+
+    fn create_test_struct(has_extra: bool) -> TestStruct {
+        TestStruct {
+            name: RawString::from("test"),
+            values: [1.0, 2.0],
+            items: vec![
+                TestItem { id: 1, extra: if has_extra { 10 } else { 0 } },
+                TestItem { id: 2, extra: if has_extra { 20 } else { 0 } },
+            ],
+        }
+    }
+
+    #[test]
+    fn derive_round_trip_test() {
+        for has_extra in [true, false] {
+            let ctx = TestContext { has_extra };
+            let value = create_test_struct(has_extra);
+
+            let mut stream = Cursor::new(Vec::new());
+            value.bin_write(&mut ByteWriter::new(&mut stream), &ctx).unwrap();
+
+            let item_size = if has_extra { 4 } else { 2 };
+            assert_eq!(8 + 4 + 8 + 4 + (2 * item_size), stream.get_ref().len());
+
+            stream.set_position(0);
+            let read_value = TestStruct::bin_read(&mut ByteReader::new(&mut stream), &ctx).unwrap();
+            assert_eq!(value, read_value);
+        }
+    }
+
+    #[test]
+    fn derive_error_path_test() {
+        let ctx = TestContext { has_extra: true };
+        let mut stream = Cursor::new(Vec::new());
+        create_test_struct(true).bin_write(&mut ByteWriter::new(&mut stream), &ctx).unwrap();
+
+        // Cut off last item extra
+        let mut data = stream.into_inner();
+        data.truncate(data.len() - 2);
+
+        let err = TestStruct::bin_read(&mut ByteReader::new(&mut Cursor::new(&data)), &ctx).unwrap_err();
+        assert!(matches!(err, OffbeatError::Parse { ref path, offset, .. } if path == "items[1].extra" && offset as usize == data.len()));
+    }
+}
\ No newline at end of file
@@ -1,30 +1,108 @@
-use crate::ByteReader;
-use std::io::{Read, Seek};
+use crate::{BinRead, BinWrite, ByteReader, ByteWriter, DdmFileRef, DdmSummary, Endian, OffbeatError, ParseTrail, RawString, TextEncoding};
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, Write};
+
+// Minimum on-disk sizes
+const MESH_SIZE: usize = 64 + 8 + 64 + 4 + 256;
+const FACE_GROUP_SIZE: usize = 8;
+const SKINNED_FACE_GROUP_SIZE: usize = 4 + 60 + FACE_GROUP_SIZE;
+const BONE_SIZE: usize = 64 + 64 + 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DdmKind {
+    #[default]
+    Static,  // Model with 32-byte sized vertices
+    Skinned, // Model with 64-byte sized vertices + bones
+}
 
-#[derive(Debug, Default)]
+impl DdmKind {
+    pub fn is_skinned(&self) -> bool {
+        matches!(self, DdmKind::Skinned)
+    }
+
+    pub fn vertex_size(&self) -> usize {
+        match self {
+            DdmKind::Static => 32,
+            DdmKind::Skinned => 64,
+        }
+    }
+
+    fn from_magic(magic: &[u8; 4]) -> Option<(DdmKind, Endian)> {
+        // Big endian builds store magic reversed
+        match magic {
+            b"srdd" => Some((DdmKind::Skinned, Endian::Little)),
+            b"mrdd" => Some((DdmKind::Static, Endian::Little)),
+            b"ddrs" => Some((DdmKind::Skinned, Endian::Big)),
+            b"ddrm" => Some((DdmKind::Static, Endian::Big)),
+            _ => None
+        }
+    }
+
+    fn magic(&self, endian: Endian) -> &'static [u8; 4] {
+        match (self, endian) {
+            (DdmKind::Skinned, Endian::Little) => b"srdd",
+            (DdmKind::Static, Endian::Little) => b"mrdd",
+            (DdmKind::Skinned, Endian::Big) => b"ddrs",
+            (DdmKind::Static, Endian::Big) => b"ddrm",
+        }
+    }
+}
+
+/// Context passed to derived readers/writers of DDM structs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DdmContext {
+    pub kind: DdmKind,
+}
+
+#[derive(Clone, Debug, Default, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[offbeat(ctx = DdmContext)]
 pub struct DdmFaceGroup {
+    #[offbeat(cond = "ctx.kind.is_skinned()")]
+    pub index_count: u32, // Used entries in bone palette
+    #[offbeat(cond = "ctx.kind.is_skinned()")]
     pub indicies: [u16; 30],
     pub triangle_start_idx: u32,
     pub triangle_count: u32,
 }
 
-#[derive(Debug, Default)]
+impl DdmFaceGroup {
+    pub fn palette(&self) -> &[u16] {
+        let size = (self.index_count as usize).min(self.indicies.len());
+        &self.indicies[..size]
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DdmMesh {
-    pub name: String,
+    pub name: RawString,
+    pub unknown_0: [u32; 2],
     pub transform: [f32; 16],
-    pub tex_name: String,
-    pub tex_ext: String,
+    pub unknown_1: u32,
+    pub tex_name: RawString,
+    pub tex_ext: RawString,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zeroed"))]
+    pub tex_padding: Vec<u8>, // Remaining bytes of 256-byte texture field
     pub face_groups: Vec<DdmFaceGroup>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[offbeat(ctx = DdmContext)]
 pub struct DdmBone {
-    pub name: String,
     pub transform: [f32; 16],
+    #[offbeat(string = 64)]
+    pub name: RawString,
     pub id: u32
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[offbeat(ctx = DdmContext)]
 pub struct DdmVertex {
     pub x: f32,
     pub y: f32,
@@ -34,168 +112,404 @@ pub struct DdmVertex {
     pub nz: f32,
     pub u: f32,
     pub v: f32,
-    pub bone_0: f32,
-    pub bone_1: f32,
-    pub bone_2: f32,
-    pub bone_3: f32,
-    pub weight_0: f32,
-    pub weight_1: f32,
-    pub weight_2: f32,
-    pub weight_3: f32,
+    #[offbeat(cond = "ctx.kind.is_skinned()")]
+    pub skin: Option<DdmSkin>, // Only present for skinned models
+}
+
+/// Bone indices + weights of skinned vertex
+#[derive(Clone, Copy, Debug, Default, PartialEq, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[offbeat(ctx = DdmContext)]
+pub struct DdmSkin {
+    pub bones: [f32; 4], // Indices into face group palette
+    pub weights: [f32; 4],
 }
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DdmFile {
+    pub endian: Endian,
+    pub encoding: TextEncoding, // Used for names without original bytes when writing
+    pub kind: DdmKind,
+    pub unknown: u32,
     pub meshes: Vec<DdmMesh>,
     pub bones: Vec<DdmBone>,
     pub triangles: Vec<u16>,
     pub vertices: Vec<DdmVertex>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    pub max_meshes: usize,
+    pub max_face_groups: usize, // Per mesh
+    pub max_bones: usize,
+    pub max_triangle_indices: usize,
+    pub max_vertices: usize,
+    pub max_allocation: usize, // In bytes
+    pub encoding: TextEncoding,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_meshes: 0x1000,
+            max_face_groups: 0x1000,
+            max_bones: 0x1000,
+            max_triangle_indices: 0x100_0000,
+            max_vertices: 0x10_0000,
+            max_allocation: 0x2000_0000, // 512 MiB
+            encoding: TextEncoding::default(),
+        }
+    }
+}
+
+pub(crate) struct ParseBudget<'a> {
+    options: &'a ParseOptions,
+    allocated: usize,
+}
+
+impl<'a> ParseBudget<'a> {
+    pub fn new(options: &'a ParseOptions) -> Self {
+        ParseBudget {
+            options,
+            allocated: 0
+        }
+    }
+
+    // Checks count against limits + remaining stream before anything is allocated
+    pub fn reserve<S, T: Read + Seek>(&mut self, reader: &mut ByteReader<T>, name: &'static str, count: usize, max: usize, stride: usize) -> Result<usize, OffbeatError> {
+        if count > max {
+            return Err(OffbeatError::CountLimitExceeded { name, count, max });
+        }
+
+        let required = (count as u64) * (stride as u64);
+        let remaining = reader.remaining()?;
+
+        if required > remaining {
+            return Err(OffbeatError::CountExceedsStream { name, count, required, remaining });
+        }
+
+        let requested = self.allocated.saturating_add(count.saturating_mul(std::mem::size_of::<S>()));
+        if requested > self.options.max_allocation {
+            return Err(OffbeatError::AllocationLimitExceeded { requested, max: self.options.max_allocation });
+        }
+
+        self.allocated = requested;
+        Ok(count)
+    }
+}
+
 impl DdmFile {
-    pub fn from_file<T: Read + Seek>(stream: &mut T) -> Result<Self, std::io::Error> {
+    pub fn from_file<T: Read + Seek>(stream: &mut T) -> Result<Self, OffbeatError> {
+        Self::from_file_with_options(stream, &ParseOptions::default())
+    }
+
+    pub fn from_file_with_options<T: Read + Seek>(stream: &mut T, options: &ParseOptions) -> Result<Self, OffbeatError> {
+        let mut reader = ByteReader::new(stream);
+        let mut trail = ParseTrail::default();
+
+        Self::read_ddm(&mut reader, options, &mut trail)
+            .map_err(|err| trail.wrap(err))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, OffbeatError> {
+        // Decode directly from slice instead of copying buffers out first
+        DdmFileRef::from_bytes(data).map(|ddm| ddm.into_file())
+    }
+
+    pub fn read_summary<T: Read + Seek>(stream: &mut T) -> Result<DdmSummary, OffbeatError> {
+        Self::read_summary_with_options(stream, &ParseOptions::default())
+    }
+
+    pub fn read_summary_with_options<T: Read + Seek>(stream: &mut T, options: &ParseOptions) -> Result<DdmSummary, OffbeatError> {
         let mut reader = ByteReader::new(stream);
+        let mut trail = ParseTrail::default();
 
+        DdmSummary::read(&mut reader, options, &mut trail)
+            .map_err(|err| trail.wrap(err))
+    }
+
+    fn read_ddm<T: Read + Seek>(reader: &mut ByteReader<T>, options: &ParseOptions, trail: &mut ParseTrail) -> Result<Self, OffbeatError> {
+        let summary = DdmSummary::read(reader, options, trail)?;
+        let triangles = summary.load_triangles(reader, options, trail)?;
+        let vertices = summary.load_vertices(reader, options, trail)?;
+
+        Ok(summary.into_file_with(triangles, vertices))
+    }
+
+    // Reads everything up to triangle + vertex buffers
+    pub(crate) fn read_tables<T: Read + Seek>(reader: &mut ByteReader<T>, options: &ParseOptions, trail: &mut ParseTrail, budget: &mut ParseBudget) -> Result<Self, OffbeatError> {
+        trail.field("magic", reader.position()?);
         let magic = reader.read_bytes::<4>()?;
 
-        // TODO: Use custom error type
-        let is_skinned = match &magic {
-            b"srdd" => true,  // Model with 64-byte sized vertices
-            b"mrdd" => false, // Model with 32-byte sized vertices
-            _ => panic!("Unsupported magic of \"{magic:?}\"")
+        let (kind, endian) = DdmKind::from_magic(&magic)
+            .ok_or(OffbeatError::BadMagic { magic })?;
+        let is_skinned = kind.is_skinned();
+
+        reader.set_endian(endian);
+        reader.set_encoding(options.encoding);
+        let ctx = DdmContext { kind };
+
+        trail.field("unknown", reader.position()?);
+        let mut ddm = DdmFile {
+            endian,
+            encoding: options.encoding,
+            kind,
+            unknown: reader.read()?,
+            ..Default::default()
         };
 
-        let mut ddm = DdmFile::default();
-        reader.skip(4)?;
-
         // Read meshes
+        trail.field("mesh_count", reader.position()?);
         let mesh_count = reader.read::<u32>()?;
-        for _ in 0..mesh_count {
-            let mut mesh = DdmMesh::default();
+        let mesh_size = if is_skinned { MESH_SIZE + 4 } else { MESH_SIZE + FACE_GROUP_SIZE };
+        check_count_endian(reader, "Mesh", mesh_count, options.max_meshes, mesh_size, &magic, endian)?;
+
+        let mesh_count = mesh_count as usize;
+        budget.reserve::<DdmMesh, _>(reader, "Mesh", mesh_count, options.max_meshes, mesh_size)?;
+
+        trail.push("meshes");
+        for mesh_idx in 0..mesh_count {
+            trail.index(mesh_idx);
 
             // Read name
-            mesh.name = reader.read_string::<64>()?;
-            reader.skip(8)?;
+            trail.field("name", reader.position()?);
+            let name = reader.read_string::<64>()?;
+
+            trail.field("unknown_0", reader.position()?);
+            let mut mesh = DdmMesh {
+                name,
+                unknown_0: reader.read()?,
+                ..Default::default()
+            };
 
             // Read transform
-            for t in mesh.transform.iter_mut() {
-                *t = reader.read()?;
-            }
+            trail.field("transform", reader.position()?);
+            mesh.transform = reader.read()?;
 
             // Read texture name + ext
-            reader.skip(4)?;
+            trail.field("unknown_1", reader.position()?);
+            mesh.unknown_1 = reader.read()?;
+
+            let tex_offset = reader.position()?;
+            trail.field("tex_name", tex_offset);
             let raw_string = reader.read_bytes::<256>()?;
-            let (tex_name, tex_ext) = split_str(&raw_string);
-            mesh.tex_name = tex_name.to_string();
-            mesh.tex_ext = tex_ext.to_string();
+            let (tex_name, tex_ext) = split_str(&raw_string)
+                .ok_or(OffbeatError::InvalidString { offset: tex_offset })?;
+            mesh.tex_padding = raw_string[(tex_name.len() + tex_ext.len() + 2)..].to_vec();
+
+            mesh.tex_name = RawString::decode(tex_name, options.encoding)
+                .ok_or(OffbeatError::InvalidString { offset: tex_offset })?;
+
+            let ext_offset = tex_offset + tex_name.len() as u64 + 1;
+            trail.field("tex_ext", ext_offset);
+            mesh.tex_ext = RawString::decode(tex_ext, options.encoding)
+                .ok_or(OffbeatError::InvalidString { offset: ext_offset })?;
 
             // Read face groups
             let group_count = if is_skinned {
-                reader.read::<u32>()?
+                trail.field("face_group_count", reader.position()?);
+                let count = reader.read::<u32>()? as usize;
+                budget.reserve::<DdmFaceGroup, _>(reader, "Face group", count, options.max_face_groups, SKINNED_FACE_GROUP_SIZE)?
             } else {
-                1u32
+                1
             };
 
-            for _ in 0..group_count {
-                let mut group = DdmFaceGroup::default();
+            trail.push("face_groups");
+            for group_idx in 0..group_count {
+                trail.index(group_idx);
+                let group = DdmFaceGroup::bin_read(reader, &ctx)?;
 
                 if is_skinned {
-                    reader.skip(4)?; // Index count
-                    for ind in group.indicies.iter_mut() {
-                        *ind = reader.read()?;
-                    }
+                    validate_palette(&mesh.name, &group);
                 }
 
-                group.triangle_start_idx = reader.read()?;
-                group.triangle_count = reader.read()?;
-
                 mesh.face_groups.push(group);
             }
+            trail.pop();
 
             ddm.meshes.push(mesh);
         }
+        trail.pop();
 
         // Read bones
         let bone_count = if is_skinned {
-            reader.read()?
+            trail.field("bone_count", reader.position()?);
+            let count = reader.read::<u32>()?;
+            check_count_endian(reader, "Bone", count, options.max_bones, BONE_SIZE, &magic, endian)?;
+            budget.reserve::<DdmBone, _>(reader, "Bone", count as usize, options.max_bones, BONE_SIZE)?
         } else {
-            0u32
+            0
         };
 
-        for _ in 0..bone_count {
-            let mut bone = DdmBone::default();
+        trail.push("bones");
+        for bone_idx in 0..bone_count {
+            trail.index(bone_idx);
+            ddm.bones.push(DdmBone::bin_read(reader, &ctx)?);
+        }
+        trail.pop();
 
-            // Read transform
-            for t in bone.transform.iter_mut() {
-                *t = reader.read()?;
+        Ok(ddm)
+    }
+
+    pub fn write_to<T: Write + Seek>(&self, stream: &mut T) -> Result<(), OffbeatError> {
+        let mut writer = ByteWriter::with_endian(stream, self.endian);
+        writer.set_encoding(self.encoding);
+        let ctx = DdmContext { kind: self.kind };
+
+        writer.write_bytes(self.kind.magic(self.endian))?;
+        writer.write(&self.unknown)?;
+
+        // Write meshes
+        writer.write(&(self.meshes.len() as u32))?;
+        for mesh in self.meshes.iter() {
+            // Write name
+            writer.write_raw_string::<64>(&mesh.name)?;
+            writer.write(&mesh.unknown_0)?;
+
+            // Write transform
+            writer.write(&mesh.transform)?;
+
+            // Write texture name + ext
+            writer.write(&mesh.unknown_1)?;
+            let mut raw_string = [
+                writer.encode_string(&mesh.tex_name)?.as_ref(),
+                b"\0",
+                writer.encode_string(&mesh.tex_ext)?.as_ref(),
+                b"\0",
+            ].concat();
+
+            // Padding is dropped where it no longer fits
+            let padding = 256usize.saturating_sub(raw_string.len()).min(mesh.tex_padding.len());
+            raw_string.extend_from_slice(&mesh.tex_padding[..padding]);
+            writer.write_fixed_bytes::<256>(&raw_string)?;
+
+            // Write face groups
+            if self.kind.is_skinned() {
+                writer.write(&(mesh.face_groups.len() as u32))?;
+            } else if mesh.face_groups.len() != 1 {
+                return Err(OffbeatError::UnsupportedFaceGroupCount { count: mesh.face_groups.len() });
             }
 
-            // Read name
-            bone.name = reader.read_string::<64>()?;
+            for group in mesh.face_groups.iter() {
+                group.bin_write(&mut writer, &ctx)?;
+            }
+        }
 
-            // Read id
-            bone.id = reader.read()?;
+        // Write bones
+        if self.kind.is_skinned() {
+            writer.write(&(self.bones.len() as u32))?;
 
-            ddm.bones.push(bone);
+            for bone in self.bones.iter() {
+                bone.bin_write(&mut writer, &ctx)?;
+            }
         }
 
-        // Read faces
-        let face_count = reader.read::<u32>()? as usize;
-        let face_buffer = reader.read_n_bytes(face_count * 2)?;
+        // Write faces
+        writer.write(&(self.triangles.len() as u32))?;
+        for tri in self.triangles.iter() {
+            writer.write(tri)?;
+        }
 
-        ddm.triangles = vec![0u16; face_count];
-        for (i, ee) in face_buffer.chunks_exact(2).enumerate() {
-            match ee {
-                &[e0, e1] => {
-                    ddm.triangles[i] = u16::from_le_bytes([e0, e1]);
-                },
-                _ => unreachable!()
-            }
+        // Write vertices
+        writer.write(&(self.vertices.len() as u32))?;
+        for v in self.vertices.iter() {
+            v.bin_write(&mut writer, &ctx)?;
         }
 
-        // Read vertices
-        let vertex_count = reader.read::<u32>()? as usize;
-        //let vertex_size = if is_skinned { 64 } else { 32 };
-        //let vertex_buffer = reader.read_n_bytes(vertex_count * vertex_size)?;
+        Ok(())
+    }
+}
 
-        ddm.vertices = vec![DdmVertex::default(); vertex_count];
-        for v in ddm.vertices.iter_mut() {
-            // Read pos
-            v.x = reader.read()?;
-            v.y = reader.read()?;
-            v.z = reader.read()?;
+pub(crate) fn decode_triangle(data: &[u8], endian: Endian) -> u16 {
+    match endian {
+        Endian::Little => u16::from_le_bytes([data[0], data[1]]),
+        Endian::Big => u16::from_be_bytes([data[0], data[1]]),
+    }
+}
 
-            // Read normals
-            v.nx = reader.read()?;
-            v.ny = reader.read()?;
-            v.nz = reader.read()?;
+pub(crate) fn decode_triangles(data: &[u8], endian: Endian) -> Vec<u16> {
+    data
+        .chunks_exact(2)
+        .map(|ee| decode_triangle(ee, endian))
+        .collect()
+}
 
-            // Read uv
-            v.u = reader.read()?;
-            v.v = reader.read()?;
+pub(crate) fn decode_vertices(data: &[u8], endian: Endian, kind: DdmKind) -> Vec<DdmVertex> {
+    data
+        .chunks_exact(kind.vertex_size())
+        .map(|chunk| decode_vertex(chunk, endian, kind))
+        .collect()
+}
 
-            if !is_skinned {
-                continue;
-            }
+pub(crate) fn decode_vertex(data: &[u8], endian: Endian, kind: DdmKind) -> DdmVertex {
+    let [x, y, z, nx, ny, nz, u, v] = decode_f32s::<8>(data, endian);
 
-            // Read bone indices
-            v.bone_0 = reader.read()?;
-            v.bone_1 = reader.read()?;
-            v.bone_2 = reader.read()?;
-            v.bone_3 = reader.read()?;
+    // Static vertices only have pos + normals + uv
+    let skin = kind.is_skinned().then(|| {
+        let [b0, b1, b2, b3, w0, w1, w2, w3] = decode_f32s::<8>(&data[32..], endian);
 
-            // Read weights
-            v.weight_0 = reader.read()?;
-            v.weight_1 = reader.read()?;
-            v.weight_2 = reader.read()?;
-            v.weight_3 = reader.read()?;
+        DdmSkin {
+            bones: [b0, b1, b2, b3],
+            weights: [w0, w1, w2, w3],
         }
+    });
 
-        Ok(ddm)
+    DdmVertex {
+        x, y, z,
+        nx, ny, nz,
+        u, v,
+        skin,
+    }
+}
+
+fn decode_f32s<const N: usize>(data: &[u8], endian: Endian) -> [f32; N] {
+    let data = &data[..(N * 4)];
+    let mut values = [0f32; N];
+
+    for (i, value) in values.iter_mut().enumerate() {
+        let bytes = [data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]];
+
+        *value = match endian {
+            Endian::Little => f32::from_le_bytes(bytes),
+            Endian::Big => f32::from_be_bytes(bytes),
+        };
     }
+
+    values
 }
 
-fn split_str<'a>(raw: &'a [u8]) -> (&'a str, &'a str) {
+fn validate_palette(mesh_name: &str, group: &DdmFaceGroup) {
+    let max_count = group.indicies.len();
+    let used_count = group.palette().len();
+
+    if group.index_count as usize > max_count {
+        warn!("Mesh \"{mesh_name}\" has face group index count of {} (max {max_count})", group.index_count);
+    } else if group.indicies[used_count..].iter().any(|i| i.ne(&0)) {
+        warn!("Mesh \"{mesh_name}\" has face group with set indices past index count of {used_count}");
+    }
+}
+
+// Byte order only comes from magic so flag counts that would only fit if swapped
+fn check_count_endian<T: Read + Seek>(reader: &mut ByteReader<T>, name: &'static str, count: u32, max: usize, stride: usize, magic: &[u8; 4], endian: Endian) -> Result<(), OffbeatError> {
+    let remaining = reader.remaining()?;
+    let fits = |count: u32| (count as usize) <= max && (count as u64) * (stride as u64) <= remaining;
+
+    if !fits(count) && fits(count.swap_bytes()) {
+        return Err(OffbeatError::EndianMismatch { name, count: count as usize, magic: *magic, endian });
+    }
+
+    Ok(())
+}
+
+// Zeroed padding is written anyway so doesn't need to be serialized
+#[cfg(feature = "serde")]
+fn is_zeroed(data: &[u8]) -> bool {
+    data.iter().all(|b| *b == 0)
+}
+
+// Splits null-separated name + ext without decoding
+fn split_str(raw: &[u8]) -> Option<(&[u8], &[u8])> {
     let mut first_size: Option<usize> = None;
     let mut second_size: Option<usize> = None;
 
@@ -217,22 +531,353 @@ fn split_str<'a>(raw: &'a [u8]) -> (&'a str, &'a str) {
         }
     }
 
-    let (s0, s1) = (first_size.unwrap(), second_size.unwrap());
+    let (s0, s1) = (first_size?, second_size?);
 
-    (
-        std::str::from_utf8(&raw[..s0]).unwrap(),
-        std::str::from_utf8(&raw[(s0 + 1)..((s0 + 1) + s1)]).unwrap()
-    )
+    Some((
+        &raw[..s0],
+        &raw[(s0 + 1)..((s0 + 1) + s1)]
+    ))
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use std::io::Cursor;
+
+    /// Face group with palette padded to 30 entries
+    pub(crate) fn create_face_group(palette: &[u16], triangle_start_idx: u32, triangle_count: u32) -> DdmFaceGroup {
+        let mut indicies = [0u16; 30];
+        indicies[..palette.len()].copy_from_slice(palette);
+
+        DdmFaceGroup {
+            index_count: palette.len() as u32,
+            indicies,
+            triangle_start_idx,
+            triangle_count,
+        }
+    }
+
+    pub(crate) fn create_skinned_vertex(bones: [f32; 4], weights: [f32; 4]) -> DdmVertex {
+        DdmVertex {
+            skin: Some(DdmSkin { bones, weights }),
+            ..Default::default()
+        }
+    }
+
+    /// In-memory file with single mesh for tests that don't need to parse
+    pub(crate) fn create_mesh_ddm(kind: DdmKind, face_groups: Vec<DdmFaceGroup>, bones: Vec<DdmBone>, triangles: Vec<u16>, vertices: Vec<DdmVertex>) -> DdmFile {
+        DdmFile {
+            kind,
+            meshes: vec![DdmMesh {
+                name: "mesh".into(),
+                face_groups,
+                ..Default::default()
+            }],
+            bones,
+            triangles,
+            vertices,
+            ..Default::default()
+        }
+    }
+
+    fn create_test_ddm(kind: DdmKind, endian: Endian) -> Vec<u8> {
+        let is_skinned = kind.is_skinned();
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::with_endian(&mut stream, endian);
+
+        writer.write_bytes(kind.magic(endian)).unwrap();
+        writer.write(&7u32).unwrap();
+
+        // Mesh
+        writer.write(&1u32).unwrap();
+        writer.write_string::<64>("body").unwrap();
+        writer.write(&1u32).unwrap();
+        writer.write(&2u32).unwrap();
+        for i in 0..16 {
+            writer.write(&(i as f32)).unwrap();
+        }
+        writer.write(&0xAABBCCDDu32).unwrap();
+        writer.write_fixed_bytes::<256>(b"body_tex\0dds\0junk").unwrap();
+
+        if is_skinned {
+            writer.write(&1u32).unwrap(); // Group count
+            writer.write(&2u32).unwrap(); // Index count
+            for i in 0..30u16 {
+                writer.write(&if i < 2 { i } else { 0 }).unwrap();
+            }
+        }
+        writer.write(&0u32).unwrap();
+        writer.write(&1u32).unwrap();
+
+        // Bones
+        if is_skinned {
+            writer.write(&2u32).unwrap();
+            for i in 0..2u32 {
+                for t in 0..16 {
+                    writer.write(&(t as f32 * 0.5)).unwrap();
+                }
+                writer.write_string::<64>(&format!("bone_{i}")).unwrap();
+                writer.write(&i).unwrap();
+            }
+        }
+
+        // Faces
+        writer.write(&3u32).unwrap();
+        for i in 0..3u16 {
+            writer.write(&i).unwrap();
+        }
+
+        // Vertices
+        writer.write(&3u32).unwrap();
+        let size = if is_skinned { 16 } else { 8 };
+        for i in 0..(3 * size) {
+            writer.write(&(i as f32)).unwrap();
+        }
+
+        stream.into_inner()
+    }
 
     #[test]
     fn split_str_test() {
-        let (str1, str2) = split_str(b"hello\0world\0");
-        assert_eq!("hello", str1);
-        assert_eq!("world", str2);
+        let (str1, str2) = split_str(b"hello\0world\0").unwrap();
+        assert_eq!(b"hello", str1);
+        assert_eq!(b"world", str2);
+    }
+
+    #[test]
+    fn split_str_missing_null_test() {
+        assert!(split_str(b"hello\0world").is_none());
+        assert!(split_str(b"hello").is_none());
+    }
+
+    #[test]
+    fn from_file_bad_magic_test() {
+        let mut stream = std::io::Cursor::new(b"abcd\0\0\0\0".to_vec());
+        let res = DdmFile::from_file(&mut stream);
+
+        assert!(matches!(res.unwrap_err().inner(), OffbeatError::BadMagic { magic } if magic == b"abcd"));
+    }
+
+    #[test]
+    fn from_file_reversed_magic_test() {
+        // Little endian data with big endian magic
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        data[..4].copy_from_slice(b"ddrs");
+
+        let err = DdmFile::from_file(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::EndianMismatch { name: "Mesh", count: 0x01000000, magic, endian: Endian::Big } if magic == b"ddrs"));
+
+        // Garbage count doesn't fit either way
+        data[8..12].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0x7F]);
+        let err = DdmFile::from_file(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::CountLimitExceeded { name: "Mesh", .. }));
+    }
+
+    #[test]
+    fn write_to_round_trip_test() {
+        for (kind, endian) in [(DdmKind::Skinned, Endian::Little), (DdmKind::Static, Endian::Little), (DdmKind::Skinned, Endian::Big), (DdmKind::Static, Endian::Big)] {
+            let data = create_test_ddm(kind, endian);
+            let ddm = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+
+            let mut stream = Cursor::new(Vec::new());
+            ddm.write_to(&mut stream).unwrap();
+
+            assert_eq!(data, stream.into_inner(), "kind: {kind:?}, endian: {endian:?}");
+        }
+    }
+
+    #[test]
+    fn write_to_name_padding_round_trip_test() {
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        data[(12 + 60)..(12 + 64)].copy_from_slice(b"junk"); // After "body\0" mesh name
+
+        let bone_name = data.windows(7).position(|w| w == b"bone_1\0").unwrap();
+        data[(bone_name + 7)..(bone_name + 10)].copy_from_slice(&[0xAB, 0xCD, 0xEF]);
+
+        let ddm = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        assert_eq!("body", ddm.meshes[0].name);
+        assert_eq!("bone_1", ddm.bones[1].name);
+
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+        assert_eq!(data, stream.into_inner());
+
+        // Padding is dropped once name is changed
+        let mut ddm = ddm;
+        ddm.meshes[0].name.set("body");
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+        assert_eq!(&[0u8; 4], &stream.into_inner()[(12 + 60)..(12 + 64)]);
+    }
+
+    #[test]
+    fn write_to_string_too_long_test() {
+        let data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+
+        let mut long_name = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        long_name.meshes[0].name.set("a".repeat(100));
+        let err = long_name.write_to(&mut Cursor::new(Vec::new())).unwrap_err();
+        assert!(matches!(err, OffbeatError::StringTooLong { max: 64, actual: 101 }));
+
+        // Name + ext with null terminators must fit in 256 bytes
+        let mut long_tex = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        long_tex.meshes[0].tex_name.set("a".repeat(255));
+        let err = long_tex.write_to(&mut Cursor::new(Vec::new())).unwrap_err();
+        assert!(matches!(err, OffbeatError::StringTooLong { max: 256, actual: 260 }));
+
+        // Longest name that fits still reads back
+        long_tex.meshes[0].tex_name.set("a".repeat(251));
+        let mut stream = Cursor::new(Vec::new());
+        long_tex.write_to(&mut stream).unwrap();
+        let loaded = DdmFile::from_file(&mut Cursor::new(stream.into_inner())).unwrap();
+        assert_eq!("a".repeat(251).as_str(), loaded.meshes[0].tex_name);
+    }
+
+    #[test]
+    fn from_file_legacy_encoding_test() {
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        data[12..18].copy_from_slice(&[0x83, 0x65, 0x83, 0x58, 0x83, 0x67]); // "テスト" mesh name
+
+        let err = DdmFile::from_file(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err, OffbeatError::Parse { ref path, offset: 12, .. } if path == "meshes[0].name"));
+
+        let options = ParseOptions {
+            encoding: TextEncoding::ShiftJis,
+            ..Default::default()
+        };
+        let ddm = DdmFile::from_file_with_options(&mut Cursor::new(&data), &options).unwrap();
+        assert_eq!("テスト", ddm.meshes[0].name);
+
+        // Original bytes are written back even if decoded lossily
+        let options = ParseOptions {
+            encoding: TextEncoding::Utf8Lossy,
+            ..Default::default()
+        };
+        let ddm = DdmFile::from_file_with_options(&mut Cursor::new(&data), &options).unwrap();
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+        assert_eq!(data, stream.into_inner());
+    }
+
+    #[test]
+    fn from_file_unknown_fields_test() {
+        let data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        let ddm = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        let mesh = &ddm.meshes[0];
+
+        assert_eq!(7, ddm.unknown);
+        assert_eq!([1, 2], mesh.unknown_0);
+        assert_eq!(0xAABBCCDD, mesh.unknown_1);
+        assert_eq!(b"junk", &mesh.tex_padding[..4]);
+        assert_eq!(&[0, 1], mesh.face_groups[0].palette());
+    }
+
+    #[test]
+    fn from_file_truncated_test() {
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        data.truncate(data.len() - 4);
+
+        let res = DdmFile::from_file(&mut Cursor::new(&data));
+        let err = res.unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::CountExceedsStream { name: "Vertex", count: 3, required: 192, remaining: 188 }));
+        assert!(matches!(err, OffbeatError::Parse { ref path, offset, .. } if path == "vertex_count" && offset as usize == data.len() - 192));
+    }
+
+    #[test]
+    fn from_file_hostile_count_test() {
+        let mut data = create_test_ddm(DdmKind::Static, Endian::Little);
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes()); // Mesh count
+
+        let res = DdmFile::from_file(&mut Cursor::new(&data));
+        assert!(matches!(res.unwrap_err().inner(), OffbeatError::CountLimitExceeded { name: "Mesh", .. }));
+    }
+
+    #[test]
+    fn from_file_allocation_limit_test() {
+        let data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        let options = ParseOptions {
+            max_allocation: 64,
+            ..Default::default()
+        };
+
+        let res = DdmFile::from_file_with_options(&mut Cursor::new(&data), &options);
+        assert!(matches!(res.unwrap_err().inner(), OffbeatError::AllocationLimitExceeded { max: 64, .. }));
+    }
+
+    #[test]
+    fn from_file_error_path_test() {
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+
+        // Remove null terminators from texture string
+        let tex_offset = 4 + 4 + 4 + 64 + 8 + 64 + 4;
+        data[tex_offset..(tex_offset + 256)].fill(b'a');
+
+        let err = DdmFile::from_file(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::InvalidString { offset } if *offset as usize == tex_offset));
+        assert!(matches!(err, OffbeatError::Parse { ref path, offset, .. } if path == "meshes[0].tex_name" && offset as usize == tex_offset));
+    }
+
+    #[test]
+    fn from_file_kind_test() {
+        let static_ddm = DdmFile::from_file(&mut Cursor::new(create_test_ddm(DdmKind::Static, Endian::Little))).unwrap();
+        let skinned_ddm = DdmFile::from_file(&mut Cursor::new(create_test_ddm(DdmKind::Skinned, Endian::Little))).unwrap();
+
+        assert_eq!(DdmKind::Static, static_ddm.kind);
+        assert!(static_ddm.vertices.iter().all(|v| v.skin.is_none()));
+
+        assert_eq!(DdmKind::Skinned, skinned_ddm.kind);
+        assert_eq!(Some([8.0, 9.0, 10.0, 11.0]), skinned_ddm.vertices[0].skin.map(|s| s.bones));
+    }
+
+    #[test]
+    fn from_file_big_endian_test() {
+        let le_ddm = DdmFile::from_file(&mut Cursor::new(create_test_ddm(DdmKind::Skinned, Endian::Little))).unwrap();
+        let be_ddm = DdmFile::from_file(&mut Cursor::new(create_test_ddm(DdmKind::Skinned, Endian::Big))).unwrap();
+
+        assert_eq!(Endian::Little, le_ddm.endian);
+        assert_eq!(Endian::Big, be_ddm.endian);
+        assert_eq!(le_ddm.unknown, be_ddm.unknown);
+        assert_eq!(le_ddm.meshes[0].transform, be_ddm.meshes[0].transform);
+        assert_eq!(le_ddm.bones[1].name, be_ddm.bones[1].name);
+        assert_eq!(le_ddm.triangles, be_ddm.triangles);
+        assert_eq!(le_ddm.vertices[2].skin, be_ddm.vertices[2].skin);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut data = create_test_ddm(DdmKind::Skinned, Endian::Little);
+        data[12..14].copy_from_slice(&[0x83, 0x65]); // Invalid UTF-8 mesh name
+        data[(12 + 60)..(12 + 64)].copy_from_slice(b"junk"); // Name padding
+
+        let options = ParseOptions {
+            encoding: TextEncoding::Utf8Lossy,
+            ..Default::default()
+        };
+        let ddm = DdmFile::from_file_with_options(&mut Cursor::new(&data), &options).unwrap();
+
+        let text = ron::to_string(&ddm).unwrap();
+        let loaded: DdmFile = ron::from_str(&text).unwrap();
+
+        let mut stream = Cursor::new(Vec::new());
+        loaded.write_to(&mut stream).unwrap();
+        assert_eq!(data, stream.into_inner());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_zeroed_padding_test() {
+        let mut data = create_test_ddm(DdmKind::Static, Endian::Little);
+        let junk = data.windows(4).position(|w| w == b"junk").unwrap();
+        data[junk..(junk + 4)].fill(0);
+
+        let ddm = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        let text = ron::to_string(&ddm).unwrap();
+        assert!(!text.contains("tex_padding"));
+
+        let loaded: DdmFile = ron::from_str(&text).unwrap();
+        let mut stream = Cursor::new(Vec::new());
+        loaded.write_to(&mut stream).unwrap();
+        assert_eq!(data, stream.into_inner());
     }
 }
\ No newline at end of file
@@ -0,0 +1,188 @@
+use crate::{ByteReader, DdmBone, DdmFile, DdmKind, DdmMesh, DdmSummary, DdmVertex, Endian, OffbeatError, ParseOptions, ParseTrail, TextEncoding, decode_triangle, decode_triangles, decode_vertex, decode_vertices};
+use std::io::Cursor;
+
+/// Borrowed view of a DDM where triangle + vertex data is decoded on demand
+#[derive(Debug)]
+pub struct DdmFileRef<'a> {
+    pub endian: Endian,
+    pub encoding: TextEncoding,
+    pub kind: DdmKind,
+    pub unknown: u32,
+    pub meshes: Vec<DdmMesh>,
+    pub bones: Vec<DdmBone>,
+    triangle_data: &'a [u8],
+    vertex_data: &'a [u8],
+    vertex_size: usize,
+}
+
+impl<'a> DdmFileRef<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, OffbeatError> {
+        Self::from_bytes_with_options(data, &ParseOptions::default())
+    }
+
+    pub fn from_bytes_with_options(data: &'a [u8], options: &ParseOptions) -> Result<Self, OffbeatError> {
+        let mut stream = Cursor::new(data);
+        let mut reader = ByteReader::new(&mut stream);
+        let mut trail = ParseTrail::default();
+
+        let summary = DdmSummary::read(&mut reader, options, &mut trail)
+            .map_err(|err| trail.wrap(err))?;
+
+        let triangle_start = summary.triangle_offset as usize;
+        let vertex_start = summary.vertex_offset as usize;
+
+        Ok(DdmFileRef {
+            triangle_data: &data[triangle_start..(triangle_start + summary.triangle_data_len())],
+            vertex_data: &data[vertex_start..(vertex_start + summary.vertex_data_len())],
+            vertex_size: summary.vertex_size(),
+            endian: summary.endian,
+            encoding: summary.encoding,
+            kind: summary.kind,
+            unknown: summary.unknown,
+            meshes: summary.meshes,
+            bones: summary.bones,
+        })
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangle_data.len() / 2
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_data.len() / self.vertex_size
+    }
+
+    pub fn triangle(&self, idx: usize) -> Option<u16> {
+        let start = idx.checked_mul(2)?;
+        let end = start.checked_add(2)?;
+
+        self.triangle_data
+            .get(start..end)
+            .map(|data| decode_triangle(data, self.endian))
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..self.triangle_count()).filter_map(|i| self.triangle(i))
+    }
+
+    pub fn vertex(&self, idx: usize) -> Option<DdmVertex> {
+        let start = idx.checked_mul(self.vertex_size)?;
+        let end = start.checked_add(self.vertex_size)?;
+
+        self.vertex_data
+            .get(start..end)
+            .map(|data| decode_vertex(data, self.endian, self.kind))
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = DdmVertex> + '_ {
+        self.vertex_data
+            .chunks_exact(self.vertex_size)
+            .map(|data| decode_vertex(data, self.endian, self.kind))
+    }
+
+    /// Raw triangle index data as stored in file
+    pub fn triangle_data(&self) -> &'a [u8] {
+        self.triangle_data
+    }
+
+    /// Raw vertex data as stored in file
+    pub fn vertex_data(&self) -> &'a [u8] {
+        self.vertex_data
+    }
+
+    pub fn to_file(&self) -> DdmFile {
+        DdmFile {
+            endian: self.endian,
+            encoding: self.encoding,
+            kind: self.kind,
+            unknown: self.unknown,
+            meshes: self.meshes.clone(),
+            bones: self.bones.clone(),
+            triangles: decode_triangles(self.triangle_data, self.endian),
+            vertices: decode_vertices(self.vertex_data, self.endian, self.kind),
+        }
+    }
+
+    pub fn into_file(self) -> DdmFile {
+        DdmFile {
+            triangles: decode_triangles(self.triangle_data, self.endian),
+            vertices: decode_vertices(self.vertex_data, self.endian, self.kind),
+            endian: self.endian,
+            encoding: self.encoding,
+            kind: self.kind,
+            unknown: self.unknown,
+            meshes: self.meshes,
+            bones: self.bones,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ByteWriter, DdmSkin, RawString};
+
+    fn create_test_data() -> Vec<u8> {
+        let ddm = DdmFile {
+            kind: DdmKind::Skinned,
+            meshes: vec![DdmMesh {
+                name: RawString::from("mesh"),
+                tex_name: RawString::from("tex"),
+                tex_ext: RawString::from("dds"),
+                face_groups: vec![Default::default()],
+                ..Default::default()
+            }],
+            triangles: vec![0, 1, 2, 2, 1, 0],
+            vertices: (0..3)
+                .map(|i| DdmVertex { x: i as f32, skin: Some(DdmSkin { weights: [0.0, 0.0, 0.0, 1.0], ..Default::default() }), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+
+        // Trailing data shouldn't be included in views
+        ByteWriter::new(&mut stream).write(&0xFFFFFFFFu32).unwrap();
+        stream.into_inner()
+    }
+
+    #[test]
+    fn from_bytes_view_test() {
+        let data = create_test_data();
+        let ddm_ref = DdmFileRef::from_bytes(&data).unwrap();
+
+        assert_eq!(6, ddm_ref.triangle_count());
+        assert_eq!(3, ddm_ref.vertex_count());
+        assert_eq!(Some(2), ddm_ref.triangle(3));
+        assert_eq!(None, ddm_ref.triangle(6));
+        assert_eq!(2.0, ddm_ref.vertex(2).unwrap().x);
+        assert!(ddm_ref.vertex(3).is_none());
+        assert_eq!(vec![0, 1, 2, 2, 1, 0], ddm_ref.triangles().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn out_of_range_index_overflow_test() {
+        let data = create_test_data();
+        let ddm_ref = DdmFileRef::from_bytes(&data).unwrap();
+
+        assert_eq!(None, ddm_ref.triangle(usize::MAX));
+        assert_eq!(None, ddm_ref.triangle(usize::MAX / 2));
+        assert!(ddm_ref.vertex(usize::MAX / 2).is_none());
+        assert!(ddm_ref.vertex(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn to_file_matches_from_file_test() {
+        let data = create_test_data();
+        let ddm = DdmFile::from_file(&mut Cursor::new(&data)).unwrap();
+        let ddm_ref = DdmFileRef::from_bytes(&data).unwrap().to_file();
+
+        assert_eq!(ddm.triangles, ddm_ref.triangles);
+        assert_eq!(ddm.vertices.len(), ddm_ref.vertices.len());
+        assert!(ddm.vertices
+            .iter()
+            .zip(ddm_ref.vertices.iter())
+            .all(|(a, b)| a.x == b.x && a.skin == b.skin));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,136 @@
+use crate::{Endian, TextEncoding};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OffbeatError {
+    #[error("Unsupported magic of \"{magic:?}\"")]
+    BadMagic { magic: [u8; 4] },
+    #[error("Invalid string at offset 0x{offset:X}")]
+    InvalidString { offset: u64 },
+    #[error("Unable to encode \"{value}\" as {encoding:?}")]
+    UnencodableString { value: String, encoding: TextEncoding },
+    #[error("String requires {actual} bytes but field only holds {max}")]
+    StringTooLong { max: usize, actual: usize },
+    #[error("Index {index} is out of range for length {len}")]
+    IndexOutOfRange { index: usize, len: usize },
+    #[error("Model doesn't have skin data")]
+    NotSkinned,
+    #[error("Transform of bone {bone} can't be inverted")]
+    SingularTransform { bone: usize },
+    #[error("Static meshes must have exactly 1 face group, found {count}")]
+    UnsupportedFaceGroupCount { count: usize },
+    #[error("{name} count of {count} exceeds limit of {max}")]
+    CountLimitExceeded { name: &'static str, count: usize, max: usize },
+    #[error("{name} count of {count} requires {required} bytes but only {remaining} remain")]
+    CountExceedsStream { name: &'static str, count: usize, required: u64, remaining: u64 },
+    #[error("{name} count of {count} doesn't fit {endian:?} byte order from magic \"{magic:?}\" but would if byte-swapped")]
+    EndianMismatch { name: &'static str, count: usize, magic: [u8; 4], endian: Endian },
+    #[error("Parsing would allocate {requested} bytes, exceeding limit of {max}")]
+    AllocationLimitExceeded { requested: usize, max: usize },
+    #[error("Failed to parse \"{path}\" at offset 0x{offset:X}: {source}")]
+    Parse { offset: u64, path: String, source: Box<OffbeatError> },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Shorthand for [`OffbeatError`]
+pub type Error = OffbeatError;
+
+impl OffbeatError {
+    /// Returns underlying error without parse location
+    pub fn inner(&self) -> &OffbeatError {
+        match self {
+            OffbeatError::Parse { source, .. } => source.inner(),
+            _ => self,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn at_field(name: &str, offset: u64, err: OffbeatError) -> OffbeatError {
+        err.with_parent(name, offset)
+    }
+
+    #[doc(hidden)]
+    pub fn at_index(idx: usize, err: OffbeatError) -> OffbeatError {
+        err.with_parent(&format!("[{idx}]"), 0)
+    }
+
+    // Prepends parent to path, keeping innermost offset
+    fn with_parent(self, parent: &str, offset: u64) -> OffbeatError {
+        match self {
+            OffbeatError::Parse { offset, path, source } => {
+                let path = match (parent, path.starts_with('[')) {
+                    ("", _) | (_, true) => format!("{parent}{path}"),
+                    _ => format!("{parent}.{path}"),
+                };
+
+                OffbeatError::Parse { offset, path, source }
+            },
+            err => OffbeatError::Parse {
+                offset,
+                path: parent.to_string(),
+                source: Box::new(err),
+            },
+        }
+    }
+}
+
+/// Tracks current location while parsing (e.g. `meshes[3].face_groups[1].triangle_count`)
+#[derive(Debug, Default)]
+pub(crate) struct ParseTrail {
+    levels: Vec<(&'static str, Option<usize>)>,
+    field: Option<&'static str>,
+    offset: u64,
+}
+
+impl ParseTrail {
+    pub fn push(&mut self, name: &'static str) {
+        self.levels.push((name, None));
+        self.field = None;
+    }
+
+    pub fn index(&mut self, idx: usize) {
+        if let Some((_, i)) = self.levels.last_mut() {
+            *i = Some(idx);
+        }
+        self.field = None;
+    }
+
+    pub fn pop(&mut self) {
+        self.levels.pop();
+        self.field = None;
+    }
+
+    pub fn field(&mut self, name: &'static str, offset: u64) {
+        self.field = Some(name);
+        self.offset = offset;
+    }
+
+    pub fn wrap(&self, err: OffbeatError) -> OffbeatError {
+        err.with_parent(&self.to_string(), self.offset)
+    }
+}
+
+impl Display for ParseTrail {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let names = self.levels
+            .iter()
+            .map(|(name, idx)| (*name, *idx))
+            .chain(self.field.map(|name| (name, None)));
+
+        for (i, (name, idx)) in names.enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+
+            write!(f, "{name}")?;
+
+            if let Some(idx) = idx {
+                write!(f, "[{idx}]")?;
+            }
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
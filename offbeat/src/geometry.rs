@@ -0,0 +1,190 @@
+use crate::{DdmFile, DdmVertex, OffbeatError};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// How face groups of mesh are split into geometry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GeometryMode {
+    #[default]
+    FaceGroups, // One geometry per face group
+    Merged,     // Single geometry with all face groups of mesh
+}
+
+/// Geometry with only referenced vertices, in order of first use
+#[derive(Clone, Debug, Default)]
+pub struct DdmGeometry<'a> {
+    pub vertices: Vec<&'a DdmVertex>,
+    pub vertex_ids: Vec<u16>,       // Original index into `DdmFile::vertices`
+    pub indices: Vec<u16>,          // Rebased into `vertices`
+    pub groups: Vec<Range<usize>>,  // Range in `indices` of each face group
+}
+
+impl<'a> DdmGeometry<'a> {
+    pub fn triangles(&self) -> impl Iterator<Item = [&'a DdmVertex; 3]> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| self.vertices[i as usize]))
+    }
+
+    fn push_indices(&mut self, indices: &[u16], vertices: &'a [DdmVertex], vert_map: &mut HashMap<u16, u16>) {
+        let start = self.indices.len();
+
+        for old_idx in indices {
+            let new_idx = *vert_map.entry(*old_idx).or_insert_with(|| {
+                self.vertices.push(&vertices[*old_idx as usize]);
+                self.vertex_ids.push(*old_idx);
+                (self.vertices.len() - 1) as u16
+            });
+
+            self.indices.push(new_idx);
+        }
+
+        self.groups.push(start..self.indices.len());
+    }
+}
+
+impl DdmFile {
+    pub fn face_group_geometry(&self, mesh_idx: usize, group_idx: usize) -> Result<DdmGeometry<'_>, OffbeatError> {
+        let indices = self.face_group_indices(mesh_idx, group_idx)?;
+
+        let mut geometry = DdmGeometry::default();
+        geometry.push_indices(indices, &self.vertices, &mut HashMap::new());
+        Ok(geometry)
+    }
+
+    pub fn mesh_geometry(&self, mesh_idx: usize, mode: GeometryMode) -> Result<Vec<DdmGeometry<'_>>, OffbeatError> {
+        let group_count = self.mesh_group_count(mesh_idx)?;
+
+        if mode == GeometryMode::FaceGroups {
+            return (0..group_count)
+                .map(|group_idx| self.face_group_geometry(mesh_idx, group_idx))
+                .collect();
+        }
+
+        // Vertices shared between face groups are only added once
+        let mut geometry = DdmGeometry::default();
+        let mut vert_map = HashMap::new();
+
+        for group_idx in 0..group_count {
+            let indices = self.face_group_indices(mesh_idx, group_idx)?;
+            geometry.push_indices(indices, &self.vertices, &mut vert_map);
+        }
+
+        Ok(vec![geometry])
+    }
+
+    /// Iterates triangles of face group without compacting vertices
+    pub fn face_group_triangles(&self, mesh_idx: usize, group_idx: usize) -> Result<impl Iterator<Item = [&DdmVertex; 3]> + '_, OffbeatError> {
+        let indices = self.face_group_indices(mesh_idx, group_idx)?;
+        Ok(self.index_triangles(indices))
+    }
+
+    /// Iterates triangles of all face groups in mesh
+    pub fn mesh_triangles(&self, mesh_idx: usize) -> Result<impl Iterator<Item = [&DdmVertex; 3]> + '_, OffbeatError> {
+        let group_count = self.mesh_group_count(mesh_idx)?;
+        let groups = (0..group_count)
+            .map(|group_idx| self.face_group_indices(mesh_idx, group_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(groups
+            .into_iter()
+            .flat_map(|indices| self.index_triangles(indices)))
+    }
+
+    /// Triangle indices of face group, checked against vertex count
+    pub(crate) fn face_group_indices(&self, mesh_idx: usize, group_idx: usize) -> Result<&[u16], OffbeatError> {
+        let mesh = self.meshes
+            .get(mesh_idx)
+            .ok_or(OffbeatError::IndexOutOfRange { index: mesh_idx, len: self.meshes.len() })?;
+
+        let group = mesh.face_groups
+            .get(group_idx)
+            .ok_or(OffbeatError::IndexOutOfRange { index: group_idx, len: mesh.face_groups.len() })?;
+
+        // 3 indicies = 1 triangle
+        let start = group.triangle_start_idx as usize;
+        let end = start + (group.triangle_count as usize * 3);
+        let indices = self.triangles
+            .get(start..end)
+            .ok_or(OffbeatError::IndexOutOfRange { index: end, len: self.triangles.len() })?;
+
+        if let Some(index) = indices.iter().find(|i| (**i as usize) >= self.vertices.len()) {
+            return Err(OffbeatError::IndexOutOfRange { index: *index as usize, len: self.vertices.len() });
+        }
+
+        Ok(indices)
+    }
+
+    fn mesh_group_count(&self, mesh_idx: usize) -> Result<usize, OffbeatError> {
+        self.meshes
+            .get(mesh_idx)
+            .map(|m| m.face_groups.len())
+            .ok_or(OffbeatError::IndexOutOfRange { index: mesh_idx, len: self.meshes.len() })
+    }
+
+    fn index_triangles<'a>(&'a self, indices: &'a [u16]) -> impl Iterator<Item = [&'a DdmVertex; 3]> + 'a {
+        indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| &self.vertices[i as usize]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddm::tests::{create_face_group, create_mesh_ddm};
+    use crate::DdmKind;
+
+    fn create_test_ddm() -> DdmFile {
+        create_mesh_ddm(
+            DdmKind::Static,
+            vec![create_face_group(&[], 0, 1), create_face_group(&[], 3, 2)],
+            Vec::new(),
+            vec![4, 2, 0, 2, 3, 4, 4, 3, 1],
+            (0..5)
+                .map(|i| DdmVertex { x: i as f32, ..Default::default() })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn face_group_geometry_test() {
+        let ddm = create_test_ddm();
+        let geometry = ddm.face_group_geometry(0, 1).unwrap();
+
+        assert_eq!(vec![2, 3, 4, 1], geometry.vertex_ids);
+        assert_eq!(vec![0, 1, 2, 2, 1, 3], geometry.indices);
+        assert_eq!(vec![0..6], geometry.groups);
+        assert_eq!(3.0, geometry.vertices[1].x);
+
+        let xs: Vec<_> = geometry.triangles().map(|t| t.map(|v| v.x)).collect();
+        assert_eq!(vec![[2.0, 3.0, 4.0], [4.0, 3.0, 1.0]], xs);
+    }
+
+    #[test]
+    fn mesh_geometry_merged_test() {
+        let ddm = create_test_ddm();
+
+        let split = ddm.mesh_geometry(0, GeometryMode::FaceGroups).unwrap();
+        assert_eq!(2, split.len());
+
+        let merged = ddm.mesh_geometry(0, GeometryMode::Merged).unwrap();
+        assert_eq!(1, merged.len());
+        assert_eq!(vec![4, 2, 0, 3, 1], merged[0].vertex_ids);
+        assert_eq!(vec![0, 1, 2, 1, 3, 0, 0, 3, 4], merged[0].indices);
+        assert_eq!(vec![0..3, 3..9], merged[0].groups);
+
+        assert_eq!(3, ddm.mesh_triangles(0).unwrap().count());
+    }
+
+    #[test]
+    fn geometry_out_of_range_test() {
+        let mut ddm = create_test_ddm();
+        ddm.triangles[5] = 9;
+
+        assert!(ddm.face_group_geometry(0, 0).is_ok());
+        assert!(matches!(ddm.face_group_geometry(0, 1), Err(OffbeatError::IndexOutOfRange { index: 9, len: 5 })));
+        assert!(matches!(ddm.face_group_triangles(0, 2), Err(OffbeatError::IndexOutOfRange { index: 2, len: 2 })));
+        assert!(ddm.mesh_triangles(0).is_err());
+    }
+}
\ No newline at end of file
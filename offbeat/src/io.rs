@@ -1,87 +1,344 @@
-use std::io::{Error as IOError, Read, Seek, SeekFrom};
+use crate::{OffbeatError, RawString, TextEncoding};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::{Error as IOError, Read, Seek, SeekFrom, Write};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
 
 pub trait Primitive : Sized {
     //fn is_sized() -> bool;
     //fn size() -> usize;
     fn from_reader<'a, T: Read + Seek>(reader: &mut ByteReader<'a, T>) -> Result<Self, IOError>;
+    fn to_writer<'a, T: Write + Seek>(&self, writer: &mut ByteWriter<'a, T>) -> Result<(), IOError>;
 }
 
-impl Primitive for f32 {
+macro_rules! impl_primitive {
+    ($($t:ty),+) => {
+        $(
+            impl Primitive for $t {
+                fn from_reader<'a, T: Read + Seek>(reader: &mut ByteReader<'a, T>) -> Result<Self, IOError> {
+                    let mut buffer = [0u8; std::mem::size_of::<Self>()];
+                    reader.read_exact(&mut buffer)?;
+
+                    match reader.endian {
+                        Endian::Little => Ok(Self::from_le_bytes(buffer)),
+                        Endian::Big => Ok(Self::from_be_bytes(buffer)),
+                    }
+                }
+
+                fn to_writer<'a, T: Write + Seek>(&self, writer: &mut ByteWriter<'a, T>) -> Result<(), IOError> {
+                    match writer.endian {
+                        Endian::Little => writer.stream.write_all(&self.to_le_bytes()),
+                        Endian::Big => writer.stream.write_all(&self.to_be_bytes()),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_primitive!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl Primitive for bool {
     fn from_reader<'a, T: Read + Seek>(reader: &mut ByteReader<'a, T>) -> Result<Self, IOError> {
-        let mut buffer = [0u8; std::mem::size_of::<Self>()];
-        reader.stream.read_exact(&mut buffer)?;
+        reader.read::<u8>().map(|v| v != 0)
+    }
 
-        Ok(Self::from_le_bytes(buffer))
+    fn to_writer<'a, T: Write + Seek>(&self, writer: &mut ByteWriter<'a, T>) -> Result<(), IOError> {
+        writer.write(&(*self as u8))
     }
 }
 
-impl Primitive for u16 {
+/// Boolean stored as 4-byte integer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bool32(pub bool);
+
+impl Primitive for Bool32 {
     fn from_reader<'a, T: Read + Seek>(reader: &mut ByteReader<'a, T>) -> Result<Self, IOError> {
-        let mut buffer = [0u8; std::mem::size_of::<Self>()];
-        reader.stream.read_exact(&mut buffer)?;
+        reader.read::<u32>().map(|v| Bool32(v != 0))
+    }
+
+    fn to_writer<'a, T: Write + Seek>(&self, writer: &mut ByteWriter<'a, T>) -> Result<(), IOError> {
+        writer.write(&(self.0 as u32))
+    }
+}
 
-        Ok(Self::from_le_bytes(buffer))
+impl From<Bool32> for bool {
+    fn from(value: Bool32) -> Self {
+        value.0
     }
 }
 
-impl Primitive for u32 {
+// Also covers matrices as nested arrays (e.g. [[f32; 4]; 4])
+impl<P: Primitive + Copy + Default, const N: usize> Primitive for [P; N] {
     fn from_reader<'a, T: Read + Seek>(reader: &mut ByteReader<'a, T>) -> Result<Self, IOError> {
-        let mut buffer = [0u8; std::mem::size_of::<Self>()];
-        reader.stream.read_exact(&mut buffer)?;
+        let mut values = [P::default(); N];
+        for value in values.iter_mut() {
+            *value = reader.read()?;
+        }
+
+        Ok(values)
+    }
+
+    fn to_writer<'a, T: Write + Seek>(&self, writer: &mut ByteWriter<'a, T>) -> Result<(), IOError> {
+        for value in self.iter() {
+            writer.write(value)?;
+        }
 
-        Ok(Self::from_le_bytes(buffer))
+        Ok(())
     }
 }
 
 pub struct ByteReader<'a, T: Read + Seek> {
     stream: &'a mut T,
+    endian: Endian,
+    encoding: TextEncoding,
+    pos: Option<u64>, // Cached stream position
 }
 
 impl<'a, T: Read + Seek> ByteReader<'a, T> {
     pub fn new(stream: &'a mut T) -> Self {
+        Self::with_endian(stream, Endian::Little)
+    }
+
+    pub fn with_endian(stream: &'a mut T, endian: Endian) -> Self {
         ByteReader {
-            stream
+            stream,
+            endian,
+            encoding: TextEncoding::default(),
+            pos: None
         }
     }
 
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.encoding = encoding;
+    }
+
     pub fn read<S: Primitive>(&mut self) -> Result<S, IOError> {
         S::from_reader(self)
     }
 
     pub fn read_bytes<const S: usize>(&mut self) -> Result<[u8; S], IOError> {
         let mut buffer = [0u8; S];
-        self.stream.read_exact(&mut buffer)?;
+        self.read_exact(&mut buffer)?;
 
         Ok(buffer)
     }
 
     pub fn read_n_bytes(&mut self, size: usize) -> Result<Vec<u8>, IOError> {
         let mut buffer = vec![0u8; size];
-        self.stream.read_exact(&mut buffer)?;
+        self.read_exact(&mut buffer)?;
 
         Ok(buffer)
     }
 
-    pub fn read_string<const S: usize>(&mut self) -> Result<String, IOError> {
+    pub fn peek<S: Primitive>(&mut self) -> Result<S, IOError> {
+        let pos = self.position()?;
+        let res = self.read();
+        self.seek(pos)?;
+
+        res
+    }
+
+    pub fn peek_bytes<const S: usize>(&mut self) -> Result<[u8; S], IOError> {
+        let pos = self.position()?;
+        let res = self.read_bytes();
+        self.seek(pos)?;
+
+        res
+    }
+
+    pub fn read_string<const S: usize>(&mut self) -> Result<RawString, OffbeatError> {
+        let offset = self.position()?;
+
         let mut buffer = [0u8; S];
-        self.stream.read_exact(&mut buffer)?;
+        self.read_exact(&mut buffer)?;
+
+        // Use whole buffer if string isn't null-terminated
+        let (raw, padding) = match buffer.iter().position(|b| *b == 0) {
+            Some(end) => (&buffer[..end], &buffer[(end + 1)..]),
+            _ => (&buffer[..], &[][..]),
+        };
 
-        // Interpret an non null-terminated string
-        if buffer.iter().all(|c| c.ne(&b'\0')) {
-            let res = std::str::from_utf8(&buffer)
-                .unwrap()
-                .to_string();
+        RawString::decode(raw, self.encoding)
+            .map(|s| s.with_padding(padding))
+            .ok_or(OffbeatError::InvalidString { offset })
+    }
 
-            return Ok(res);
+    pub fn position(&mut self) -> Result<u64, IOError> {
+        match self.pos {
+            Some(pos) => Ok(pos),
+            None => {
+                let pos = self.stream.stream_position()?;
+                self.pos = Some(pos);
+                Ok(pos)
+            }
         }
+    }
+
+    pub fn len(&mut self) -> Result<u64, IOError> {
+        let pos = self.position()?;
+        self.seek_from(SeekFrom::End(0))?;
+        let len = self.position()?;
+        self.seek(pos)?;
+
+        Ok(len)
+    }
+
+    pub fn is_empty(&mut self) -> Result<bool, IOError> {
+        self.len().map(|len| len == 0)
+    }
 
-        let res = std::ffi::CStr::from_bytes_until_nul(&buffer)
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string();
+    pub fn remaining(&mut self) -> Result<u64, IOError> {
+        let pos = self.position()?;
+        let len = self.len()?;
 
-        Ok(res)
+        Ok(len.saturating_sub(pos))
+    }
+
+    pub fn seek(&mut self, offset: u64) -> Result<(), IOError> {
+        self.seek_from(SeekFrom::Start(offset))
+    }
+
+    pub fn skip(&mut self, offset: i64) -> Result<(), IOError> {
+        self.seek_from(SeekFrom::Current(offset))
+    }
+
+    fn seek_from(&mut self, pos: SeekFrom) -> Result<(), IOError> {
+        self.pos = None;
+        self.pos = Some(self.stream.seek(pos)?);
+
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), IOError> {
+        if let Err(err) = self.stream.read_exact(buffer) {
+            // Position is unspecified after failed read
+            self.pos = None;
+            return Err(err);
+        }
+
+        if let Some(pos) = self.pos.as_mut() {
+            *pos += buffer.len() as u64;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct ByteWriter<'a, T: Write + Seek> {
+    stream: &'a mut T,
+    endian: Endian,
+    encoding: TextEncoding,
+}
+
+impl<'a, T: Write + Seek> ByteWriter<'a, T> {
+    pub fn new(stream: &'a mut T) -> Self {
+        Self::with_endian(stream, Endian::Little)
+    }
+
+    pub fn with_endian(stream: &'a mut T, endian: Endian) -> Self {
+        ByteWriter {
+            stream,
+            endian,
+            encoding: TextEncoding::default(),
+        }
+    }
+
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.encoding = encoding;
+    }
+
+    pub fn write<S: Primitive>(&mut self, value: &S) -> Result<(), IOError> {
+        value.to_writer(self)
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) -> Result<(), IOError> {
+        self.stream.write_all(data)
+    }
+
+    pub fn write_fixed_bytes<const S: usize>(&mut self, data: &[u8]) -> Result<(), OffbeatError> {
+        if data.len() > S {
+            return Err(OffbeatError::StringTooLong { max: S, actual: data.len() });
+        }
+
+        // Zero-pad to fixed size
+        let mut buffer = [0u8; S];
+        buffer[..data.len()].copy_from_slice(data);
+
+        Ok(self.stream.write_all(&buffer)?)
+    }
+
+    pub fn write_string<const S: usize>(&mut self, value: &str) -> Result<(), OffbeatError> {
+        self.write_raw_string::<S>(&RawString::new(value))
+    }
+
+    /// Writes original bytes of string if unchanged since read
+    pub fn write_raw_string<const S: usize>(&mut self, value: &RawString) -> Result<(), OffbeatError> {
+        let encoded = self.encode_string(value)?;
+
+        // Original string may fill whole field without null terminator
+        if value.raw().is_some() && encoded.len() == S {
+            return self.write_fixed_bytes::<S>(&encoded);
+        }
+
+        let size = encoded.len() + 1;
+        let padding = value.padding();
+        let padding = &padding[..padding.len().min(S.saturating_sub(size))];
+
+        self.write_fixed_bytes::<S>(&[&encoded[..], b"\0", padding].concat())
+    }
+
+    pub fn encode_string<'b>(&self, value: &'b RawString) -> Result<Cow<'b, [u8]>, OffbeatError> {
+        value
+            .to_bytes(self.encoding)
+            .ok_or_else(|| OffbeatError::UnencodableString { value: value.to_string(), encoding: self.encoding })
+    }
+
+    pub fn align(&mut self, alignment: u64) -> Result<(), IOError> {
+        if alignment == 0 {
+            return Ok(());
+        }
+
+        let pos = self.position()?;
+        let padding = (alignment - (pos % alignment)) % alignment;
+
+        self.stream.write_all(&vec![0u8; padding as usize])
+    }
+
+    pub fn position(&mut self) -> Result<u64, IOError> {
+        self.stream.stream_position()
     }
 
     pub fn seek(&mut self, offset: u64) -> Result<(), IOError> {
@@ -91,4 +348,141 @@ impl<'a, T: Read + Seek> ByteReader<'a, T> {
     pub fn skip(&mut self, offset: i64) -> Result<(), IOError> {
         self.stream.seek(SeekFrom::Current(offset)).map(|_| ())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn write_read_primitives_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::new(&mut stream);
+        writer.write(&0x1234u16).unwrap();
+        writer.write(&0xDEADBEEFu32).unwrap();
+        writer.write(&1.5f32).unwrap();
+
+        stream.set_position(0);
+        let mut reader = ByteReader::new(&mut stream);
+        assert_eq!(0x1234u16, reader.read().unwrap());
+        assert_eq!(0xDEADBEEFu32, reader.read().unwrap());
+        assert_eq!(1.5f32, reader.read().unwrap());
+    }
+
+    #[test]
+    fn write_read_extended_primitives_test() {
+        for endian in [Endian::Little, Endian::Big] {
+            let mut stream = Cursor::new(Vec::new());
+            let mut writer = ByteWriter::with_endian(&mut stream, endian);
+            writer.write(&-2i8).unwrap();
+            writer.write(&-0x1234i16).unwrap();
+            writer.write(&i64::MIN).unwrap();
+            writer.write(&u64::MAX).unwrap();
+            writer.write(&0.25f64).unwrap();
+            writer.write(&true).unwrap();
+            writer.write(&Bool32(true)).unwrap();
+            writer.write(&[1u16, 2, 3]).unwrap();
+            writer.write(&[[1.0f32, 2.0], [3.0, 4.0]]).unwrap();
+            assert_eq!(1 + 2 + 8 + 8 + 8 + 1 + 4 + 6 + 16, stream.get_ref().len());
+
+            stream.set_position(0);
+            let mut reader = ByteReader::with_endian(&mut stream, endian);
+            assert_eq!(-2i8, reader.read().unwrap());
+            assert_eq!(-0x1234i16, reader.read().unwrap());
+            assert_eq!(i64::MIN, reader.read().unwrap());
+            assert_eq!(u64::MAX, reader.read().unwrap());
+            assert_eq!(0.25f64, reader.read().unwrap());
+            assert!(reader.read::<bool>().unwrap());
+            assert_eq!(Bool32(true), reader.read().unwrap());
+            assert_eq!([1u16, 2, 3], reader.read::<[u16; 3]>().unwrap());
+            assert_eq!([[1.0f32, 2.0], [3.0, 4.0]], reader.read::<[[f32; 2]; 2]>().unwrap());
+        }
+    }
+
+    #[test]
+    fn position_test() {
+        let mut stream = Cursor::new(vec![1u8, 0, 2, 0, 3, 0]);
+        let mut reader = ByteReader::new(&mut stream);
+
+        assert_eq!(6, reader.len().unwrap());
+        assert_eq!(1u16, reader.peek().unwrap());
+        assert_eq!(1u16, reader.read().unwrap());
+        assert_eq!([2, 0], reader.peek_bytes::<2>().unwrap());
+        assert_eq!(2, reader.position().unwrap());
+        assert_eq!(4, reader.remaining().unwrap());
+
+        reader.skip(2).unwrap();
+        assert_eq!(3u16, reader.read().unwrap());
+        assert_eq!(0, reader.remaining().unwrap());
+        assert!(reader.read::<u16>().is_err());
+    }
+
+    #[test]
+    fn big_endian_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::with_endian(&mut stream, Endian::Big);
+        writer.write(&0x1234u16).unwrap();
+        writer.write(&0xDEADBEEFu32).unwrap();
+        assert_eq!(&[0x12, 0x34, 0xDE, 0xAD, 0xBE, 0xEF], stream.get_ref().as_slice());
+
+        stream.set_position(0);
+        let mut reader = ByteReader::with_endian(&mut stream, Endian::Big);
+        assert_eq!(0x1234u16, reader.read().unwrap());
+        assert_eq!(0xDEADBEEFu32, reader.read().unwrap());
+    }
+
+    #[test]
+    fn write_string_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::new(&mut stream);
+        writer.write_string::<8>("hello").unwrap();
+        writer.write_string::<6>("world").unwrap();
+
+        assert_eq!(b"hello\0\0\0world\0", stream.get_ref().as_slice());
+    }
+
+    #[test]
+    fn write_string_too_long_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::new(&mut stream);
+
+        // No room for null terminator
+        let res = writer.write_string::<5>("world");
+        assert!(matches!(res, Err(OffbeatError::StringTooLong { max: 5, actual: 6 })));
+
+        let res = writer.write_fixed_bytes::<4>(b"world");
+        assert!(matches!(res, Err(OffbeatError::StringTooLong { max: 4, actual: 5 })));
+        assert!(stream.get_ref().is_empty());
+    }
+
+    #[test]
+    fn string_encoding_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::new(&mut stream);
+        writer.set_encoding(TextEncoding::ShiftJis);
+        writer.write_string::<8>("テスト").unwrap();
+        writer.set_encoding(TextEncoding::Latin1);
+        assert!(matches!(writer.write_string::<8>("テスト"), Err(OffbeatError::UnencodableString { .. })));
+
+        stream.set_position(0);
+        let mut reader = ByteReader::new(&mut stream);
+        assert!(matches!(reader.read_string::<8>(), Err(OffbeatError::InvalidString { offset: 0 })));
+
+        reader.seek(0).unwrap();
+        reader.set_encoding(TextEncoding::ShiftJis);
+        assert_eq!("テスト", reader.read_string::<8>().unwrap());
+    }
+
+    #[test]
+    fn align_test() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut writer = ByteWriter::new(&mut stream);
+        writer.write_bytes(&[1, 2, 3]).unwrap();
+        writer.align(4).unwrap();
+        writer.align(4).unwrap();
+        writer.align(0).unwrap();
+
+        assert_eq!(&[1, 2, 3, 0], stream.get_ref().as_slice());
+    }
 }
\ No newline at end of file
@@ -1,5 +1,30 @@
+extern crate self as offbeat; // Allows derives to be used within crate
+
+mod binary;
 mod ddm;
+mod ddm_ref;
+mod error;
+mod geometry;
 mod io;
+mod math;
+mod palette;
+pub mod skeleton;
+mod stats;
+mod summary;
+mod text;
+mod validate;
 
+pub use binary::*;
 pub use ddm::*;
-pub(crate) use io::*;
\ No newline at end of file
+pub use ddm_ref::*;
+pub use error::*;
+pub use geometry::*;
+pub use io::*;
+pub use math::*;
+pub use offbeat_derive::{BinRead, BinWrite};
+pub use palette::*;
+pub use skeleton::*;
+pub use stats::*;
+pub use summary::*;
+pub use text::*;
+pub use validate::*;
\ No newline at end of file
@@ -0,0 +1,366 @@
+//! Transform convention of DDM files
+//!
+//! Matrices are stored as 16 `f32` in row-vector order (rows are basis vectors, translation in
+//! last row). That's memory-identical to column-major matrices using column vectors, which is
+//! how `Mat4` and glTF treat them, so values can be used as-is. Coordinates are left-handed,
+//! converting to right-handed (glTF) negates X of points + normals and uses
+//! `Mat4::to_right_handed` for transforms.
+
+use crate::{DdmBone, DdmMesh, DdmVertex};
+use std::ops::Mul;
+
+/// Column-major 4x4 matrix (translation in elements 12..15)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4(pub [f32; 16]);
+
+/// Decomposed transform with rotation as quaternion (x, y, z, w)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trs {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4([
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ]);
+
+    pub fn from_translation(translation: [f32; 3]) -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        m.0[12..15].copy_from_slice(&translation);
+        m
+    }
+
+    pub fn from_trs(trs: &Trs) -> Mat4 {
+        let [x, y, z, w] = trs.rotation;
+        let [sx, sy, sz] = trs.scale;
+        let [tx, ty, tz] = trs.translation;
+
+        Mat4([
+            (1.0 - 2.0 * (y * y + z * z)) * sx, 2.0 * (x * y + z * w) * sx, 2.0 * (x * z - y * w) * sx, 0.0,
+            2.0 * (x * y - z * w) * sy, (1.0 - 2.0 * (x * x + z * z)) * sy, 2.0 * (y * z + x * w) * sy, 0.0,
+            2.0 * (x * z + y * w) * sz, 2.0 * (y * z - x * w) * sz, (1.0 - 2.0 * (x * x + y * y)) * sz, 0.0,
+            tx, ty, tz, 1.0,
+        ])
+    }
+
+    pub fn col(&self, idx: usize) -> [f32; 4] {
+        let c = &self.0[(idx * 4)..(idx * 4 + 4)];
+        [c[0], c[1], c[2], c[3]]
+    }
+
+    pub fn translation(&self) -> [f32; 3] {
+        [self.0[12], self.0[13], self.0[14]]
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        let mut out = [0.0; 16];
+
+        for col in 0..4 {
+            for row in 0..4 {
+                out[col * 4 + row] = self.0[row * 4 + col];
+            }
+        }
+
+        Mat4(out)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.cofactors().1
+    }
+
+    pub fn inverse(&self) -> Option<Mat4> {
+        let (inv, det) = self.cofactors();
+
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        Some(Mat4(inv.map(|v| v / det)))
+    }
+
+    // Adjugate + determinant by cofactor expansion
+    fn cofactors(&self) -> ([f32; 16], f32) {
+        let m = &self.0;
+        let mut inv = [0.0; 16];
+
+        inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15] + m[9] * m[7] * m[14] + m[13] * m[6] * m[11] - m[13] * m[7] * m[10];
+        inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15] - m[8] * m[7] * m[14] - m[12] * m[6] * m[11] + m[12] * m[7] * m[10];
+        inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15] + m[8] * m[7] * m[13] + m[12] * m[5] * m[11] - m[12] * m[7] * m[9];
+        inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14] - m[8] * m[6] * m[13] - m[12] * m[5] * m[10] + m[12] * m[6] * m[9];
+        inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15] - m[9] * m[3] * m[14] - m[13] * m[2] * m[11] + m[13] * m[3] * m[10];
+        inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15] + m[8] * m[3] * m[14] + m[12] * m[2] * m[11] - m[12] * m[3] * m[10];
+        inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15] - m[8] * m[3] * m[13] - m[12] * m[1] * m[11] + m[12] * m[3] * m[9];
+        inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14] + m[8] * m[2] * m[13] + m[12] * m[1] * m[10] - m[12] * m[2] * m[9];
+        inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15] + m[5] * m[3] * m[14] + m[13] * m[2] * m[7] - m[13] * m[3] * m[6];
+        inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15] - m[4] * m[3] * m[14] - m[12] * m[2] * m[7] + m[12] * m[3] * m[6];
+        inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15] + m[4] * m[3] * m[13] + m[12] * m[1] * m[7] - m[12] * m[3] * m[5];
+        inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14] - m[4] * m[2] * m[13] - m[12] * m[1] * m[6] + m[12] * m[2] * m[5];
+        inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11] - m[5] * m[3] * m[10] - m[9] * m[2] * m[7] + m[9] * m[3] * m[6];
+        inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11] + m[4] * m[3] * m[10] + m[8] * m[2] * m[7] - m[8] * m[3] * m[6];
+        inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11] - m[4] * m[3] * m[9] - m[8] * m[1] * m[7] + m[8] * m[3] * m[5];
+        inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10] + m[4] * m[2] * m[9] + m[8] * m[1] * m[6] - m[8] * m[2] * m[5];
+
+        let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+        (inv, det)
+    }
+
+    /// Splits into translation, rotation + scale (assumes no shear)
+    pub fn decompose(&self) -> Trs {
+        let length = |c: [f32; 4]| (c[0] * c[0] + c[1] * c[1] + c[2] * c[2]).sqrt();
+        let mut scale = [length(self.col(0)), length(self.col(1)), length(self.col(2))];
+
+        // Mirrored transforms get negative x scale
+        if self.determinant() < 0.0 {
+            scale[0] = -scale[0];
+        }
+
+        let axis = |idx: usize| {
+            let c = self.col(idx);
+            let s = if scale[idx] != 0.0 { scale[idx] } else { 1.0 };
+            [c[0] / s, c[1] / s, c[2] / s]
+        };
+        let ([m00, m10, m20], [m01, m11, m21], [m02, m12, m22]) = (axis(0), axis(1), axis(2));
+
+        // Rotation matrix -> quaternion
+        let trace = m00 + m11 + m22;
+        let rotation = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            [(m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s]
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            [0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s]
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            [(m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s]
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            [(m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s]
+        };
+
+        Trs {
+            translation: self.translation(),
+            rotation,
+            scale,
+        }
+    }
+
+    /// Decomposes only if TRS reproduces matrix (no shear or projection)
+    pub fn try_decompose(&self) -> Option<Trs> {
+        let trs = self.decompose();
+        let composed = Mat4::from_trs(&trs);
+
+        let is_near = self.0
+            .iter()
+            .zip(composed.0.iter())
+            .all(|(a, b)| (a - b).abs() <= 1e-4 * a.abs().max(1.0));
+
+        is_near.then_some(trs)
+    }
+
+    pub fn transform_point(&self, p: [f32; 3]) -> [f32; 3] {
+        let m = &self.0;
+        [
+            m[0] * p[0] + m[4] * p[1] + m[8] * p[2] + m[12],
+            m[1] * p[0] + m[5] * p[1] + m[9] * p[2] + m[13],
+            m[2] * p[0] + m[6] * p[1] + m[10] * p[2] + m[14],
+        ]
+    }
+
+    pub fn transform_vector(&self, v: [f32; 3]) -> [f32; 3] {
+        let m = &self.0;
+        [
+            m[0] * v[0] + m[4] * v[1] + m[8] * v[2],
+            m[1] * v[0] + m[5] * v[1] + m[9] * v[2],
+            m[2] * v[0] + m[6] * v[1] + m[10] * v[2],
+        ]
+    }
+
+    /// Transforms normal with inverse transpose and renormalizes
+    pub fn transform_normal(&self, n: [f32; 3]) -> [f32; 3] {
+        let normal_matrix = self.inverse().map(|m| m.transpose()).unwrap_or(*self);
+        normalize(normal_matrix.transform_vector(n))
+    }
+
+    /// Converts transform between left + right handed spaces by mirroring X axis
+    pub fn to_right_handed(&self) -> Mat4 {
+        let mirror = Mat4::mirror_x();
+        mirror * *self * mirror
+    }
+
+    pub fn mirror_x() -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        m.0[0] = -1.0;
+        m
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        let (a, b) = (&self.0, &rhs.0);
+        let mut out = [0.0; 16];
+
+        for col in 0..4 {
+            for row in 0..4 {
+                out[col * 4 + row] = (0..4)
+                    .map(|k| a[k * 4 + row] * b[col * 4 + k])
+                    .sum();
+            }
+        }
+
+        Mat4(out)
+    }
+}
+
+impl From<[f32; 16]> for Mat4 {
+    fn from(value: [f32; 16]) -> Self {
+        Mat4(value)
+    }
+}
+
+impl From<Mat4> for [f32; 16] {
+    fn from(value: Mat4) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Mat4> for glam::Mat4 {
+    fn from(value: Mat4) -> Self {
+        glam::Mat4::from_cols_array(&value.0)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Mat4> for Mat4 {
+    fn from(value: glam::Mat4) -> Self {
+        Mat4(value.to_cols_array())
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Mat4> for mint::ColumnMatrix4<f32> {
+    fn from(value: Mat4) -> Self {
+        let c = |i: usize| mint::Vector4::from(value.col(i));
+
+        mint::ColumnMatrix4 { x: c(0), y: c(1), z: c(2), w: c(3) }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::ColumnMatrix4<f32>> for Mat4 {
+    fn from(value: mint::ColumnMatrix4<f32>) -> Self {
+        let cols: [[f32; 4]; 4] = [value.x.into(), value.y.into(), value.z.into(), value.w.into()];
+        Mat4(std::array::from_fn(|i| cols[i / 4][i % 4]))
+    }
+}
+
+impl DdmMesh {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4(self.transform)
+    }
+}
+
+impl DdmBone {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4(self.transform)
+    }
+}
+
+impl DdmVertex {
+    pub fn position(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn normal(&self) -> [f32; 3] {
+        [self.nx, self.ny, self.nz]
+    }
+
+    /// Returns copy with position + normal transformed
+    pub fn transformed(&self, m: &Mat4) -> DdmVertex {
+        let [x, y, z] = m.transform_point(self.position());
+        let [nx, ny, nz] = m.transform_normal(self.normal());
+
+        DdmVertex { x, y, z, nx, ny, nz, ..self.clone() }
+    }
+}
+
+pub(crate) fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let [x, y, z] = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    (x * x + y * y + z * z).sqrt()
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+
+    if length == 0.0 {
+        return v;
+    }
+
+    v.map(|c| c / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: &[f32], b: &[f32]) {
+        assert!(a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-5), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn trs_round_trip_test() {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let trs = Trs {
+            translation: [1.0, 2.0, 3.0],
+            rotation: [0.0, half, 0.0, half], // 90 degrees around Y
+            scale: [2.0, 1.0, 0.5],
+        };
+
+        let m = Mat4::from_trs(&trs);
+        let decomposed = m.decompose();
+        assert_near(&trs.translation, &decomposed.translation);
+        assert_near(&trs.rotation, &decomposed.rotation);
+        assert_near(&trs.scale, &decomposed.scale);
+
+        assert_eq!(Some(decomposed), m.try_decompose());
+
+        assert_near(&[1.0, 2.0, -1.0], &m.transform_point([2.0, 0.0, 0.0]));
+        assert_near(&Mat4::IDENTITY.0, &(m * m.inverse().unwrap()).0);
+    }
+
+    #[test]
+    fn try_decompose_shear_test() {
+        let mut m = Mat4::IDENTITY;
+        m.0[4] = 0.5; // Y axis leans into X
+
+        assert_eq!(None, m.try_decompose());
+        assert!(Mat4::from_translation([1.0, 2.0, 3.0]).try_decompose().is_some());
+    }
+
+    #[test]
+    fn transform_vertex_test() {
+        let m = Mat4::from_trs(&Trs {
+            translation: [0.0, 5.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [2.0, 1.0, 1.0],
+        });
+
+        let v = DdmVertex { x: 1.0, nx: 1.0, ny: 1.0, ..Default::default() }.transformed(&m);
+        assert_near(&[2.0, 5.0, 0.0], &v.position());
+        assert_near(&[0.5 / (1.25f32).sqrt(), 1.0 / (1.25f32).sqrt(), 0.0], &v.normal());
+        assert_near(&[-1.0, 5.0, 0.0], &Mat4::from_translation([1.0, 5.0, 0.0]).to_right_handed().translation());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,154 @@
+use crate::{DdmFile, OffbeatError};
+use std::collections::{BTreeMap, HashMap};
+
+/// How face group palette values map to `DdmFile::bones`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoneMapping {
+    #[default]
+    Position, // Palette value is position in bone table
+    Id,       // Palette value matches `DdmBone::id`
+}
+
+/// Bones of vertex as indices into `DdmFile::bones`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VertexInfluence {
+    pub bones: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteIssue {
+    InvalidSlot { vertex: u16, value: f32 },        // Slot isn't integer in 0..30
+    UnsetSlot { vertex: u16, slot: usize },         // Slot is past index count of group
+    UnknownBone { vertex: u16, slot: usize, value: u16 },
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ResolvedPalette {
+    pub influences: BTreeMap<u16, VertexInfluence>, // Keyed by vertex index
+    pub issues: Vec<PaletteIssue>,
+}
+
+impl DdmFile {
+    /// Resolves bone influences of vertices referenced by face group
+    ///
+    /// Influences that can't be resolved are reported in issues and written with zero weight.
+    pub fn resolve_palette(&self, mesh_idx: usize, group_idx: usize, mapping: BoneMapping) -> Result<ResolvedPalette, OffbeatError> {
+        if !self.kind.is_skinned() {
+            return Err(OffbeatError::NotSkinned);
+        }
+
+        let indices = self.face_group_indices(mesh_idx, group_idx)?;
+        let group = &self.meshes[mesh_idx].face_groups[group_idx];
+
+        let bone_ids: HashMap<u32, usize> = match mapping {
+            BoneMapping::Position => HashMap::new(),
+            BoneMapping::Id => self.bones
+                .iter()
+                .enumerate()
+                .map(|(i, b)| (b.id, i))
+                .collect(),
+        };
+
+        let resolve_bone = |value: u16| match mapping {
+            BoneMapping::Position => ((value as usize) < self.bones.len()).then_some(value),
+            BoneMapping::Id => bone_ids.get(&(value as u32)).map(|i| *i as u16),
+        };
+
+        let mut resolved = ResolvedPalette::default();
+
+        for vertex in indices.iter() {
+            if resolved.influences.contains_key(vertex) {
+                continue;
+            }
+
+            let skin = self.vertices[*vertex as usize]
+                .skin
+                .unwrap_or_default();
+
+            let mut influence = VertexInfluence::default();
+
+            for (i, (slot_value, weight)) in skin.bones.iter().zip(skin.weights.iter()).enumerate() {
+                // Unused influences can point anywhere
+                if *weight == 0.0 {
+                    continue;
+                }
+
+                let slot = *slot_value as usize;
+                let issue = if slot_value.fract() != 0.0 || *slot_value < 0.0 || slot >= group.indicies.len() {
+                    Some(PaletteIssue::InvalidSlot { vertex: *vertex, value: *slot_value })
+                } else if slot >= group.palette().len() {
+                    Some(PaletteIssue::UnsetSlot { vertex: *vertex, slot })
+                } else {
+                    let value = group.indicies[slot];
+
+                    match resolve_bone(value) {
+                        Some(bone) => {
+                            influence.bones[i] = bone;
+                            influence.weights[i] = *weight;
+                            None
+                        },
+                        None => Some(PaletteIssue::UnknownBone { vertex: *vertex, slot, value }),
+                    }
+                };
+
+                if let Some(issue) = issue {
+                    resolved.issues.push(issue);
+                }
+            }
+
+            resolved.influences.insert(*vertex, influence);
+        }
+
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddm::tests::{create_face_group, create_mesh_ddm, create_skinned_vertex};
+    use crate::{DdmBone, DdmKind};
+
+    fn create_test_ddm() -> DdmFile {
+        create_mesh_ddm(
+            DdmKind::Skinned,
+            vec![create_face_group(&[2, 0, 7], 0, 1)],
+            [7, 0, 2].into_iter().map(|id| DdmBone { id, ..Default::default() }).collect(),
+            vec![0, 1, 0],
+            vec![
+                create_skinned_vertex([0.0, 1.0, 0.0, 0.0], [0.5, 0.5, 0.0, 0.0]),
+                create_skinned_vertex([2.0, 5.0, 0.5, 0.0], [0.5, 0.25, 0.25, 0.0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn resolve_palette_by_id_test() {
+        let ddm = create_test_ddm();
+        let resolved = ddm.resolve_palette(0, 0, BoneMapping::Id).unwrap();
+
+        assert_eq!(2, resolved.influences.len());
+        assert_eq!([2, 1, 0, 0], resolved.influences[&0].bones);
+        assert_eq!([0.5, 0.5, 0.0, 0.0], resolved.influences[&0].weights);
+        assert_eq!([0, 0, 0, 0], resolved.influences[&1].bones);
+        assert_eq!([0.5, 0.0, 0.0, 0.0], resolved.influences[&1].weights);
+        assert_eq!(
+            vec![
+                PaletteIssue::UnsetSlot { vertex: 1, slot: 5 },
+                PaletteIssue::InvalidSlot { vertex: 1, value: 0.5 },
+            ],
+            resolved.issues
+        );
+    }
+
+    #[test]
+    fn resolve_palette_by_position_test() {
+        let ddm = create_test_ddm();
+        let resolved = ddm.resolve_palette(0, 0, BoneMapping::Position).unwrap();
+
+        assert_eq!([2, 0, 0, 0], resolved.influences[&0].bones);
+        assert_eq!(Some(&PaletteIssue::UnknownBone { vertex: 1, slot: 2, value: 7 }), resolved.issues.first());
+        assert!(matches!(ddm.resolve_palette(1, 0, BoneMapping::Position), Err(OffbeatError::IndexOutOfRange { index: 1, len: 1 })));
+    }
+}
\ No newline at end of file
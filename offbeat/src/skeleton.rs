@@ -0,0 +1,278 @@
+use crate::{BoneMapping, DdmFile, Mat4, OffbeatError, math};
+use log::warn;
+
+/// Where parent links of bones are taken from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParentSource {
+    #[default]
+    Transforms, // Closest preceding bone in bind pose, if local transform relative to it is TRS
+    None,       // Every bone is a root
+}
+
+/// What `DdmBone::transform` stores
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoneTransformKind {
+    #[default]
+    Bind,        // Bone -> model space
+    InverseBind, // Model -> bone space
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SkeletonOptions {
+    pub parents: ParentSource,
+    pub transform_kind: Option<BoneTransformKind>, // Detected from skinned vertices if not set
+    pub mapping: BoneMapping, // Used to resolve face group palettes for detection
+}
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub name: String,
+    pub id: u32,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub local: Mat4, // Relative to parent
+    pub world: Mat4, // Bind pose in model space
+    pub inverse_bind: Mat4,
+}
+
+/// Bone hierarchy with joints in same order as `DdmFile::bones`
+#[derive(Clone, Debug, Default)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    pub roots: Vec<usize>,
+    pub transform_kind: BoneTransformKind,
+}
+
+impl Skeleton {
+    pub fn from_ddm(ddm: &DdmFile) -> Result<Self, OffbeatError> {
+        Self::from_ddm_with_options(ddm, &SkeletonOptions::default())
+    }
+
+    pub fn from_ddm_with_options(ddm: &DdmFile, options: &SkeletonOptions) -> Result<Self, OffbeatError> {
+        let transform_kind = options.transform_kind
+            .unwrap_or_else(|| BoneTransformKind::detect(ddm, options.mapping));
+
+        // Compute bind pose + inverse of each bone
+        let mut joints = Vec::new();
+        for (i, bone) in ddm.bones.iter().enumerate() {
+            let transform = bone.matrix();
+            let inverse = transform
+                .inverse()
+                .ok_or(OffbeatError::SingularTransform { bone: i })?;
+
+            let (world, inverse_bind) = match transform_kind {
+                BoneTransformKind::Bind => (transform, inverse),
+                BoneTransformKind::InverseBind => (inverse, transform),
+            };
+
+            joints.push(Joint {
+                name: bone.name.to_string(),
+                id: bone.id,
+                parent: None,
+                children: Vec::new(),
+                local: world,
+                world,
+                inverse_bind,
+            });
+        }
+
+        let parents = find_parents(&joints, options.parents);
+
+        for (i, parent) in parents.into_iter().enumerate() {
+            let Some(parent) = parent else {
+                continue;
+            };
+
+            joints[i].parent = Some(parent);
+            joints[i].local = joints[parent].inverse_bind * joints[i].world;
+            joints[parent].children.push(i);
+        }
+
+        let roots = joints
+            .iter()
+            .enumerate()
+            .filter(|(_, j)| j.parent.is_none())
+            .map(|(i, _)| i)
+            .collect();
+
+        Ok(Skeleton {
+            joints,
+            roots,
+            transform_kind,
+        })
+    }
+
+    /// Joint indices ordered so parents come before children
+    pub fn depth_first(&self) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut stack: Vec<usize> = self.roots.iter().rev().copied().collect();
+
+        while let Some(i) = stack.pop() {
+            order.push(i);
+            stack.extend(self.joints[i].children.iter().rev());
+        }
+
+        order
+    }
+}
+
+impl BoneTransformKind {
+    /// Guesses kind by checking which interpretation places bones closer to vertices they influence
+    pub fn detect(ddm: &DdmFile, mapping: BoneMapping) -> BoneTransformKind {
+        let bind_positions: Vec<_> = ddm.bones
+            .iter()
+            .map(|b| b.matrix().translation())
+            .collect();
+
+        let inverse_positions: Vec<_> = ddm.bones
+            .iter()
+            .zip(bind_positions.iter())
+            .map(|(b, p)| b.matrix().inverse().map(|m| m.translation()).unwrap_or(*p))
+            .collect();
+
+        let (mut bind_distance, mut inverse_distance) = (0.0, 0.0);
+
+        for (mesh_idx, mesh) in ddm.meshes.iter().enumerate() {
+            for group_idx in 0..mesh.face_groups.len() {
+                let Ok(resolved) = ddm.resolve_palette(mesh_idx, group_idx, mapping) else {
+                    continue;
+                };
+
+                for (vertex, influence) in resolved.influences.iter() {
+                    // Use bone with highest weight
+                    let Some((bone, _)) = influence.bones
+                        .iter()
+                        .zip(influence.weights.iter())
+                        .filter(|(_, w)| **w > 0.0)
+                        .max_by(|(_, a), (_, b)| a.total_cmp(b)) else {
+                        continue;
+                    };
+
+                    let v = &ddm.vertices[*vertex as usize];
+                    bind_distance += math::distance(v.position(), bind_positions[*bone as usize]);
+                    inverse_distance += math::distance(v.position(), inverse_positions[*bone as usize]);
+                }
+            }
+        }
+
+        if inverse_distance < bind_distance {
+            BoneTransformKind::InverseBind
+        } else {
+            BoneTransformKind::Bind
+        }
+    }
+}
+
+// Format doesn't store parents (`DdmBone::id` is unique id, not parent link) so they're guessed
+fn find_parents(joints: &[Joint], source: ParentSource) -> Vec<Option<usize>> {
+    match source {
+        ParentSource::None => vec![None; joints.len()],
+        ParentSource::Transforms => joints
+            .iter()
+            .enumerate()
+            .map(|(i, j)| {
+                let pos = j.world.translation();
+
+                let parent = joints[..i]
+                    .iter()
+                    .enumerate()
+                    .map(|(p, parent)| (p, math::distance(pos, parent.world.translation())))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(p, _)| p)?;
+
+                // Parent world * local has to compose back to world, otherwise bone is a root
+                let local = joints[parent].inverse_bind * j.world;
+                if local.try_decompose().is_none() {
+                    warn!("Bone \"{}\" can't be expressed relative to \"{}\", treating as root", j.name, joints[parent].name);
+                    return None;
+                }
+
+                Some(parent)
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddm::tests::{create_face_group, create_mesh_ddm, create_skinned_vertex};
+    use crate::{DdmBone, DdmKind, DdmVertex};
+
+    fn create_test_ddm(inverse: bool) -> DdmFile {
+        let positions = [[0.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 4.0, 0.0], [3.0, 2.0, 0.0]];
+
+        create_mesh_ddm(
+            DdmKind::Skinned,
+            vec![create_face_group(&[0, 1, 2, 3], 0, 2)],
+            positions
+                .iter()
+                .enumerate()
+                .map(|(i, [x, y, z])| DdmBone {
+                    name: format!("bone_{i}").into(),
+                    transform: Mat4::from_translation(if inverse { [-x, -y, -z] } else { [*x, *y, *z] }).0,
+                    id: i as u32,
+                })
+                .collect(),
+            vec![0, 1, 2, 3, 2, 1],
+            // Each vertex sits close to the bone it's weighted to
+            positions
+                .iter()
+                .enumerate()
+                .map(|(i, [x, y, z])| DdmVertex {
+                    x: x + 0.1,
+                    y: *y,
+                    z: *z,
+                    ..create_skinned_vertex([i as f32, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0])
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn skeleton_from_transforms_test() {
+        let skeleton = Skeleton::from_ddm(&create_test_ddm(false)).unwrap();
+
+        assert_eq!(BoneTransformKind::Bind, skeleton.transform_kind);
+        assert_eq!(vec![0], skeleton.roots);
+        assert_eq!(vec![None, Some(0), Some(1), Some(1)], skeleton.joints.iter().map(|j| j.parent).collect::<Vec<_>>());
+        assert_eq!(vec![0, 1, 2, 3], skeleton.depth_first());
+        assert_eq!([3.0, 0.0, 0.0], skeleton.joints[3].local.translation());
+        assert_eq!([-3.0, -2.0, 0.0], skeleton.joints[3].inverse_bind.translation());
+    }
+
+    #[test]
+    fn skeleton_inverse_bind_test() {
+        let skeleton = Skeleton::from_ddm(&create_test_ddm(true)).unwrap();
+
+        assert_eq!(BoneTransformKind::InverseBind, skeleton.transform_kind);
+        assert_eq!(vec![None, Some(0), Some(1), Some(1)], skeleton.joints.iter().map(|j| j.parent).collect::<Vec<_>>());
+        assert_eq!([0.0, 4.0, 0.0], skeleton.joints[2].world.translation());
+        assert_eq!([0.0, 2.0, 0.0], skeleton.joints[2].local.translation());
+    }
+
+    #[test]
+    fn skeleton_sheared_parent_test() {
+        let mut ddm = create_test_ddm(false);
+        ddm.bones[1].transform[0] = 2.0; // Non-uniform scale
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        ddm.bones[2].transform[..8].copy_from_slice(&[half, half, 0.0, 0.0, -half, half, 0.0, 0.0]); // 45 degrees around z
+
+        let options = SkeletonOptions {
+            transform_kind: Some(BoneTransformKind::Bind),
+            ..Default::default()
+        };
+        let skeleton = Skeleton::from_ddm_with_options(&ddm, &options).unwrap();
+
+        // Rotated bone would need shear relative to scaled one
+        assert_eq!(vec![0, 2], skeleton.roots);
+        assert_eq!(vec![None, Some(0), None, Some(1)], skeleton.joints.iter().map(|j| j.parent).collect::<Vec<_>>());
+
+        let options = SkeletonOptions {
+            parents: ParentSource::None,
+            ..options
+        };
+        let skeleton = Skeleton::from_ddm_with_options(&ddm, &options).unwrap();
+        assert_eq!(vec![0, 1, 2, 3], skeleton.roots);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,238 @@
+use crate::{BoneMapping, DdmFile, OffbeatError, VertexInfluence};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundingSphere {
+    pub center: [f32; 3], // Center of AABB
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UvRange {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GeometryStats {
+    pub aabb: Option<Aabb>, // None if no vertices
+    pub sphere: Option<BoundingSphere>,
+    pub uv_range: Option<UvRange>,
+    pub triangle_count: usize,
+    pub vertex_count: usize, // Unique vertices referenced by triangles
+    pub degenerate_triangle_count: usize,
+    pub max_influences: usize, // Non-zero weights of single vertex
+    pub bone_count: usize,     // Distinct bones with non-zero weight
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MeshStats {
+    pub name: String,
+    pub geometry: GeometryStats,
+    pub face_groups: Vec<GeometryStats>,
+}
+
+/// Statistics of whole file, meshes and face groups
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DdmStats {
+    pub geometry: GeometryStats,
+    pub meshes: Vec<MeshStats>,
+}
+
+impl UvRange {
+    /// Returns true if coordinates go outside 0..1
+    pub fn is_tiling(&self) -> bool {
+        self.min.iter().any(|v| *v < 0.0) || self.max.iter().any(|v| *v > 1.0)
+    }
+}
+
+impl DdmFile {
+    /// Computes statistics, mapping is used to resolve bones of skinned models
+    pub fn stats(&self, mapping: BoneMapping) -> Result<DdmStats, OffbeatError> {
+        let mut all_groups = Vec::new();
+        let mut meshes = Vec::new();
+
+        for (mesh_idx, mesh) in self.meshes.iter().enumerate() {
+            let groups: Vec<_> = (0..mesh.face_groups.len())
+                .map(|group_idx| (mesh_idx, group_idx))
+                .collect();
+
+            let face_groups = groups
+                .iter()
+                .map(|g| self.geometry_stats(&[*g], mapping))
+                .collect::<Result<_, _>>()?;
+
+            meshes.push(MeshStats {
+                name: mesh.name.to_string(),
+                geometry: self.geometry_stats(&groups, mapping)?,
+                face_groups,
+            });
+
+            all_groups.extend(groups);
+        }
+
+        Ok(DdmStats {
+            geometry: self.geometry_stats(&all_groups, mapping)?,
+            meshes,
+        })
+    }
+
+    fn geometry_stats(&self, groups: &[(usize, usize)], mapping: BoneMapping) -> Result<GeometryStats, OffbeatError> {
+        let mut stats = GeometryStats::default();
+        let mut vertex_ids = BTreeSet::new();
+        let mut influences: HashMap<u16, Vec<VertexInfluence>> = HashMap::new();
+
+        for (mesh_idx, group_idx) in groups.iter().copied() {
+            let indices = self.face_group_indices(mesh_idx, group_idx)?;
+
+            for t in indices.chunks_exact(3) {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| self.vertices[i as usize].position());
+
+                // Zero area also covers repeated indices
+                let (ab, ac) = (sub(b, a), sub(c, a));
+                let cross = [
+                    ab[1] * ac[2] - ab[2] * ac[1],
+                    ab[2] * ac[0] - ab[0] * ac[2],
+                    ab[0] * ac[1] - ab[1] * ac[0],
+                ];
+
+                if cross.iter().all(|v| *v == 0.0) {
+                    stats.degenerate_triangle_count += 1;
+                }
+            }
+
+            stats.triangle_count += indices.len() / 3;
+            vertex_ids.extend(indices.iter().copied());
+
+            if self.kind.is_skinned() {
+                // Same vertex can have different bones in each face group
+                for (vertex, influence) in self.resolve_palette(mesh_idx, group_idx, mapping)?.influences {
+                    influences.entry(vertex).or_default().push(influence);
+                }
+            }
+        }
+
+        stats.vertex_count = vertex_ids.len();
+
+        for id in vertex_ids.iter() {
+            let v = &self.vertices[*id as usize];
+            let pos = v.position();
+
+            let aabb = stats.aabb.get_or_insert(Aabb { min: pos, max: pos });
+            let uv = stats.uv_range.get_or_insert(UvRange { min: [v.u, v.v], max: [v.u, v.v] });
+
+            for (i, value) in pos.into_iter().enumerate() {
+                aabb.min[i] = aabb.min[i].min(value);
+                aabb.max[i] = aabb.max[i].max(value);
+            }
+
+            for (i, value) in [v.u, v.v].into_iter().enumerate() {
+                uv.min[i] = uv.min[i].min(value);
+                uv.max[i] = uv.max[i].max(value);
+            }
+        }
+
+        stats.sphere = stats.aabb.map(|aabb| {
+            let center = [0, 1, 2].map(|i| (aabb.min[i] + aabb.max[i]) / 2.0);
+            let radius = vertex_ids
+                .iter()
+                .map(|id| crate::math::distance(center, self.vertices[*id as usize].position()))
+                .fold(0.0, f32::max);
+
+            BoundingSphere { center, radius }
+        });
+
+        let mut bones = BTreeSet::new();
+        for influence in influences.values().flatten() {
+            let used: Vec<_> = influence.bones
+                .iter()
+                .zip(influence.weights.iter())
+                .filter(|(_, w)| **w > 0.0)
+                .map(|(b, _)| *b)
+                .collect();
+
+            stats.max_influences = stats.max_influences.max(used.len());
+            bones.extend(used);
+        }
+
+        stats.bone_count = bones.len();
+        Ok(stats)
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddm::tests::{create_face_group, create_mesh_ddm, create_skinned_vertex};
+    use crate::{DdmBone, DdmKind, DdmVertex};
+
+    fn create_test_ddm() -> DdmFile {
+        let vertex = |x, y, u, bones, weights| DdmVertex {
+            x,
+            y,
+            u,
+            ..create_skinned_vertex(bones, weights)
+        };
+
+        create_mesh_ddm(
+            DdmKind::Skinned,
+            vec![create_face_group(&[0, 1], 0, 1), create_face_group(&[2], 3, 1)],
+            vec![DdmBone::default(); 3],
+            vec![0, 1, 2, 2, 3, 2],
+            vec![
+                vertex(0.0, 0.0, 0.0, [0.0, 1.0, 0.0, 0.0], [0.5, 0.5, 0.0, 0.0]),
+                vertex(4.0, 0.0, 1.0, [0.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]),
+                vertex(0.0, 2.0, 2.0, [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]),
+                vertex(0.0, -2.0, 0.5, [0.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn stats_test() {
+        let ddm = create_test_ddm();
+        let stats = ddm.stats(BoneMapping::Position).unwrap();
+
+        let group = &stats.meshes[0].face_groups[1];
+        assert_eq!(1, group.degenerate_triangle_count);
+        assert_eq!(2, group.vertex_count);
+        assert_eq!(1, group.bone_count);
+
+        let file = &stats.geometry;
+        assert_eq!(2, file.triangle_count);
+        assert_eq!(4, file.vertex_count);
+        assert_eq!(1, file.degenerate_triangle_count);
+        assert_eq!(Some(Aabb { min: [0.0, -2.0, 0.0], max: [4.0, 2.0, 0.0] }), file.aabb);
+        assert_eq!(Some(BoundingSphere { center: [2.0, 0.0, 0.0], radius: 8.0f32.sqrt() }), file.sphere);
+        assert_eq!(Some(UvRange { min: [0.0, 0.0], max: [2.0, 0.0] }), file.uv_range);
+        assert!(file.uv_range.unwrap().is_tiling());
+        assert_eq!(2, file.max_influences);
+        assert_eq!(3, file.bone_count); // Vertex 2 uses bone 1 in first group, bone 2 in second
+        assert_eq!(stats.geometry, stats.meshes[0].geometry);
+    }
+
+    #[test]
+    fn stats_empty_test() {
+        let stats = DdmFile::default().stats(BoneMapping::Position).unwrap();
+        assert_eq!(GeometryStats::default(), stats.geometry);
+        assert!(stats.meshes.is_empty());
+    }
+}
\ No newline at end of file
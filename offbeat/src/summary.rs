@@ -0,0 +1,217 @@
+use crate::{ByteReader, DdmBone, DdmFile, DdmKind, DdmMesh, DdmVertex, Endian, OffbeatError, ParseBudget, ParseOptions, ParseTrail, TextEncoding, decode_triangles, decode_vertices};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek};
+
+/// Mesh + bone tables of a DDM with triangle + vertex buffers left in stream until needed
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DdmSummary {
+    pub endian: Endian,
+    pub encoding: TextEncoding,
+    pub kind: DdmKind,
+    pub unknown: u32,
+    pub meshes: Vec<DdmMesh>,
+    pub bones: Vec<DdmBone>,
+    pub triangle_offset: u64,
+    pub triangle_count: usize, // Index count (3 per triangle)
+    pub vertex_offset: u64,
+    pub vertex_count: usize,
+}
+
+impl DdmSummary {
+    pub(crate) fn read<T: Read + Seek>(reader: &mut ByteReader<T>, options: &ParseOptions, trail: &mut ParseTrail) -> Result<Self, OffbeatError> {
+        let mut budget = ParseBudget::new(options);
+        let ddm = DdmFile::read_tables(reader, options, trail, &mut budget)?;
+
+        // Read buffer counts and skip over data
+        trail.field("triangle_count", reader.position()?);
+        let triangle_count = reader.read::<u32>()? as usize;
+        budget.reserve::<u16, _>(reader, "Triangle index", triangle_count, options.max_triangle_indices, 2)?;
+
+        let triangle_offset = reader.position()?;
+        reader.skip((triangle_count * 2) as i64)?;
+
+        let vertex_size = ddm.kind.vertex_size();
+
+        trail.field("vertex_count", reader.position()?);
+        let vertex_count = reader.read::<u32>()? as usize;
+        budget.reserve::<DdmVertex, _>(reader, "Vertex", vertex_count, options.max_vertices, vertex_size)?;
+
+        let vertex_offset = reader.position()?;
+        reader.skip((vertex_count * vertex_size) as i64)?;
+
+        Ok(DdmSummary {
+            endian: ddm.endian,
+            encoding: ddm.encoding,
+            kind: ddm.kind,
+            unknown: ddm.unknown,
+            meshes: ddm.meshes,
+            bones: ddm.bones,
+            triangle_offset,
+            triangle_count,
+            vertex_offset,
+            vertex_count,
+        })
+    }
+
+    pub fn vertex_size(&self) -> usize {
+        self.kind.vertex_size()
+    }
+
+    pub fn triangle_data_len(&self) -> usize {
+        self.triangle_count.saturating_mul(2)
+    }
+
+    pub fn vertex_data_len(&self) -> usize {
+        self.vertex_count.saturating_mul(self.vertex_size())
+    }
+
+    pub fn read_triangles<T: Read + Seek>(&self, stream: &mut T) -> Result<Vec<u16>, OffbeatError> {
+        self.read_triangles_with_options(stream, &ParseOptions::default())
+    }
+
+    pub fn read_triangles_with_options<T: Read + Seek>(&self, stream: &mut T, options: &ParseOptions) -> Result<Vec<u16>, OffbeatError> {
+        let mut reader = ByteReader::with_endian(stream, self.endian);
+        let mut trail = ParseTrail::default();
+
+        self.load_triangles(&mut reader, options, &mut trail)
+            .map_err(|err| trail.wrap(err))
+    }
+
+    pub fn read_vertices<T: Read + Seek>(&self, stream: &mut T) -> Result<Vec<DdmVertex>, OffbeatError> {
+        self.read_vertices_with_options(stream, &ParseOptions::default())
+    }
+
+    pub fn read_vertices_with_options<T: Read + Seek>(&self, stream: &mut T, options: &ParseOptions) -> Result<Vec<DdmVertex>, OffbeatError> {
+        let mut reader = ByteReader::with_endian(stream, self.endian);
+        let mut trail = ParseTrail::default();
+
+        self.load_vertices(&mut reader, options, &mut trail)
+            .map_err(|err| trail.wrap(err))
+    }
+
+    /// Loads remaining buffers from same stream summary was read from
+    pub fn into_file<T: Read + Seek>(self, stream: &mut T) -> Result<DdmFile, OffbeatError> {
+        self.into_file_with_options(stream, &ParseOptions::default())
+    }
+
+    pub fn into_file_with_options<T: Read + Seek>(self, stream: &mut T, options: &ParseOptions) -> Result<DdmFile, OffbeatError> {
+        let triangles = self.read_triangles_with_options(stream, options)?;
+        let vertices = self.read_vertices_with_options(stream, options)?;
+
+        Ok(self.into_file_with(triangles, vertices))
+    }
+
+    // Counts + offsets are public (or deserialized) so are checked again before allocating
+    pub(crate) fn load_triangles<T: Read + Seek>(&self, reader: &mut ByteReader<T>, options: &ParseOptions, trail: &mut ParseTrail) -> Result<Vec<u16>, OffbeatError> {
+        trail.field("triangles", self.triangle_offset);
+        reader.seek(self.triangle_offset)?;
+        ParseBudget::new(options).reserve::<u16, _>(reader, "Triangle index", self.triangle_count, options.max_triangle_indices, 2)?;
+
+        let data = reader.read_n_bytes(self.triangle_data_len())?;
+        Ok(decode_triangles(&data, self.endian))
+    }
+
+    pub(crate) fn load_vertices<T: Read + Seek>(&self, reader: &mut ByteReader<T>, options: &ParseOptions, trail: &mut ParseTrail) -> Result<Vec<DdmVertex>, OffbeatError> {
+        trail.field("vertices", self.vertex_offset);
+        reader.seek(self.vertex_offset)?;
+        ParseBudget::new(options).reserve::<DdmVertex, _>(reader, "Vertex", self.vertex_count, options.max_vertices, self.vertex_size())?;
+
+        let data = reader.read_n_bytes(self.vertex_data_len())?;
+        Ok(decode_vertices(&data, self.endian, self.kind))
+    }
+
+    pub(crate) fn into_file_with(self, triangles: Vec<u16>, vertices: Vec<DdmVertex>) -> DdmFile {
+        DdmFile {
+            endian: self.endian,
+            encoding: self.encoding,
+            kind: self.kind,
+            unknown: self.unknown,
+            meshes: self.meshes,
+            bones: self.bones,
+            triangles,
+            vertices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawString;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_summary_test() {
+        let ddm = DdmFile {
+            meshes: vec![DdmMesh {
+                name: RawString::from("mesh"),
+                tex_name: RawString::from("tex"),
+                tex_ext: RawString::from("dds"),
+                face_groups: vec![Default::default()],
+                ..Default::default()
+            }],
+            triangles: vec![0, 1, 2],
+            vertices: (0..3)
+                .map(|i| DdmVertex { x: i as f32, ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+        let data_len = stream.get_ref().len() as u64;
+        stream.set_position(0);
+
+        let summary = DdmFile::read_summary(&mut stream).unwrap();
+        assert_eq!("tex", summary.meshes[0].tex_name);
+        assert_eq!(3, summary.triangle_count);
+        assert_eq!(3, summary.vertex_count);
+        assert_eq!(data_len, summary.vertex_offset + summary.vertex_data_len() as u64);
+
+        // Buffers are loaded on demand
+        assert_eq!(vec![0, 1, 2], summary.read_triangles(&mut stream).unwrap());
+        assert_eq!(2.0, summary.read_vertices(&mut stream).unwrap()[2].x);
+
+        let loaded = summary.into_file(&mut stream).unwrap();
+        assert_eq!(ddm.triangles, loaded.triangles);
+        assert_eq!(ddm.vertices.len(), loaded.vertices.len());
+    }
+
+    #[test]
+    fn read_buffers_untrusted_counts_test() {
+        let ddm = DdmFile {
+            meshes: vec![DdmMesh {
+                face_groups: vec![Default::default()],
+                ..Default::default()
+            }],
+            triangles: vec![0, 1, 2],
+            vertices: vec![DdmVertex::default(); 3],
+            ..Default::default()
+        };
+
+        let mut stream = Cursor::new(Vec::new());
+        ddm.write_to(&mut stream).unwrap();
+        stream.set_position(0);
+
+        // E.g. summary deserialized from untrusted source
+        let mut summary = DdmFile::read_summary(&mut stream).unwrap();
+        summary.triangle_count = usize::MAX;
+        summary.vertex_count = 4;
+
+        let err = summary.read_triangles(&mut stream).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::CountLimitExceeded { name: "Triangle index", .. }));
+
+        let err = summary.read_vertices(&mut stream).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::CountExceedsStream { name: "Vertex", count: 4, .. }));
+
+        let options = ParseOptions {
+            max_vertices: 2,
+            ..Default::default()
+        };
+        summary.vertex_count = 3;
+        let err = summary.read_vertices_with_options(&mut stream, &options).unwrap_err();
+        assert!(matches!(err.inner(), OffbeatError::CountLimitExceeded { name: "Vertex", count: 3, max: 2 }));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,253 @@
+use encoding_rs::SHIFT_JIS;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+
+/// Policy used to decode + encode fixed-size name fields
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextEncoding {
+    #[default]
+    Utf8,      // Invalid sequences are an error
+    Utf8Lossy, // Invalid sequences are replaced with U+FFFD
+    Latin1,
+    ShiftJis,
+}
+
+impl TextEncoding {
+    pub fn decode(&self, raw: &[u8]) -> Option<String> {
+        match self {
+            TextEncoding::Utf8 => std::str::from_utf8(raw).ok().map(|s| s.to_string()),
+            TextEncoding::Utf8Lossy => Some(String::from_utf8_lossy(raw).into_owned()),
+            TextEncoding::Latin1 => Some(raw.iter().map(|b| *b as char).collect()),
+            TextEncoding::ShiftJis => SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(raw)
+                .map(|s| s.into_owned()),
+        }
+    }
+
+    pub fn encode<'a>(&self, value: &'a str) -> Option<Cow<'a, [u8]>> {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Lossy => Some(Cow::Borrowed(value.as_bytes())),
+            TextEncoding::Latin1 => value
+                .chars()
+                .map(|c| u8::try_from(c).ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Cow::Owned),
+            TextEncoding::ShiftJis => {
+                let (data, _, had_errors) = SHIFT_JIS.encode(value);
+                (!had_errors).then_some(data)
+            },
+        }
+    }
+}
+
+/// Decoded string that keeps original bytes so it can be written back unchanged
+#[derive(Clone, Debug, Default)]
+pub struct RawString {
+    value: String,
+    raw: Option<Vec<u8>>, // Cleared when value is changed
+    padding: Vec<u8>, // Bytes after null terminator in fixed-size field
+}
+
+impl RawString {
+    pub fn new<S: Into<String>>(value: S) -> Self {
+        RawString {
+            value: value.into(),
+            raw: None,
+            padding: Vec::new(),
+        }
+    }
+
+    pub fn decode(raw: &[u8], encoding: TextEncoding) -> Option<Self> {
+        encoding.decode(raw).map(|value| RawString {
+            value,
+            raw: Some(raw.to_vec()),
+            padding: Vec::new(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Original bytes if string was read from file and not changed since
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// Bytes after null terminator if string was read from fixed-size field and not changed since
+    pub fn padding(&self) -> &[u8] {
+        &self.padding
+    }
+
+    pub(crate) fn with_padding(mut self, padding: &[u8]) -> Self {
+        self.padding = padding.to_vec();
+        self
+    }
+
+    pub fn set<S: Into<String>>(&mut self, value: S) {
+        self.value = value.into();
+        self.raw = None;
+        self.padding.clear();
+    }
+
+    /// Returns original bytes, otherwise value encoded with given encoding
+    pub fn to_bytes(&self, encoding: TextEncoding) -> Option<Cow<'_, [u8]>> {
+        match &self.raw {
+            Some(raw) => Some(Cow::Borrowed(raw)),
+            None => encoding.encode(&self.value),
+        }
+    }
+}
+
+impl Deref for RawString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl Display for RawString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl From<&str> for RawString {
+    fn from(value: &str) -> Self {
+        RawString::new(value)
+    }
+}
+
+impl From<String> for RawString {
+    fn from(value: String) -> Self {
+        RawString::new(value)
+    }
+}
+
+// Compares decoded values only
+impl PartialEq for RawString {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for RawString {}
+
+impl PartialEq<str> for RawString {
+    fn eq(&self, other: &str) -> bool {
+        self.value == other
+    }
+}
+
+impl PartialEq<&str> for RawString {
+    fn eq(&self, other: &&str) -> bool {
+        self.value == *other
+    }
+}
+
+impl PartialEq<RawString> for &str {
+    fn eq(&self, other: &RawString) -> bool {
+        *self == other.value
+    }
+}
+
+// Plain string unless original bytes differ from UTF-8 of value
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawStringRepr {
+    Value(String),
+    Raw {
+        value: String,
+        raw: Vec<u8>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        padding: Vec<u8>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for RawString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Zeroed padding is written anyway so can be dropped
+        let has_padding = self.padding.iter().any(|b| *b != 0);
+
+        let repr = match &self.raw {
+            Some(raw) if has_padding || raw != self.value.as_bytes() => RawStringRepr::Raw {
+                value: self.value.clone(),
+                raw: raw.clone(),
+                padding: if has_padding { self.padding.clone() } else { Vec::new() },
+            },
+            _ => RawStringRepr::Value(self.value.clone()),
+        };
+
+        repr.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RawString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match RawStringRepr::deserialize(deserializer)? {
+            RawStringRepr::Value(value) => RawString::new(value),
+            RawStringRepr::Raw { value, raw, padding } => {
+                // Drop bytes if value was edited by hand
+                let matches = [TextEncoding::Utf8Lossy, TextEncoding::Latin1, TextEncoding::ShiftJis]
+                    .iter()
+                    .any(|e| e.decode(&raw).as_deref() == Some(value.as_str()));
+
+                RawString {
+                    value,
+                    raw: matches.then_some(raw),
+                    padding: if matches { padding } else { Vec::new() },
+                }
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_encode_test() {
+        let sjis = [0x83, 0x65, 0x83, 0x58, 0x83, 0x67]; // "テスト"
+
+        assert_eq!(Some(String::from("テスト")), TextEncoding::ShiftJis.decode(&sjis));
+        assert_eq!(Some(&sjis[..]), TextEncoding::ShiftJis.encode("テスト").as_deref());
+        assert_eq!(None, TextEncoding::Utf8.decode(&sjis));
+        assert_eq!(Some(String::from("caf\u{e9}")), TextEncoding::Latin1.decode(b"caf\xE9"));
+        assert_eq!(None, TextEncoding::Latin1.encode("テスト"));
+        assert_eq!(Some(String::from("a\u{FFFD}")), TextEncoding::Utf8Lossy.decode(b"a\xFF"));
+    }
+
+    #[test]
+    fn raw_string_keeps_bytes_test() {
+        let mut value = RawString::decode(b"a\xFF", TextEncoding::Utf8Lossy).unwrap();
+        assert_eq!(Some(&b"a\xFF"[..]), value.to_bytes(TextEncoding::Utf8).as_deref());
+
+        value.set("b");
+        assert_eq!(None, value.raw());
+        assert_eq!(Some(&b"b"[..]), value.to_bytes(TextEncoding::Utf8).as_deref());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn raw_string_serde_test() {
+        let value = RawString::decode(b"a\xFF", TextEncoding::Utf8Lossy).unwrap();
+        let text = ron::to_string(&value).unwrap();
+        assert_eq!(Some(&b"a\xFF"[..]), ron::from_str::<RawString>(&text).unwrap().raw());
+
+        // Bytes no longer match edited value
+        let edited: RawString = ron::from_str(r#"(value: "b", raw: [97, 255])"#).unwrap();
+        assert_eq!("b", edited);
+        assert_eq!(None, edited.raw());
+
+        assert_eq!("\"c\"", ron::to_string(&RawString::decode(b"c", TextEncoding::Utf8).unwrap()).unwrap());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,241 @@
+use crate::{DdmFile, DdmKind, DdmVertex};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const WEIGHT_SUM_TOLERANCE: f32 = 0.01;
+const NORMAL_LENGTH_TOLERANCE: f32 = 0.01;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum DdmDiagnostic {
+    #[error("Mesh {mesh} face group {group} references triangle indices {start}..{end} but only {len} exist")]
+    FaceGroupOutOfRange { mesh: usize, group: usize, start: usize, end: usize, len: usize },
+    #[error("Triangle index {position} references vertex {index} but only {vertex_count} exist")]
+    TriangleIndexOutOfRange { position: usize, index: u16, vertex_count: usize },
+    #[error("Mesh {mesh} face group {group} palette slot {slot} references bone {index} but only {bone_count} exist")]
+    PaletteIndexOutOfRange { mesh: usize, group: usize, slot: usize, index: u16, bone_count: usize },
+    #[error("Vertex {vertex} has non-integral bone index {value}")]
+    NonIntegralBoneIndex { vertex: usize, value: f32 },
+    #[error("Vertex {vertex} has weights summing to {sum}")]
+    InvalidWeightSum { vertex: usize, sum: f32 },
+    #[error("Vertex {vertex} has non-finite position")]
+    NonFinitePosition { vertex: usize },
+    #[error("Vertex {vertex} has normal of length {length}")]
+    NonUnitNormal { vertex: usize, length: f32 },
+    #[error("Vertex {vertex} skin data doesn't match {kind:?} model")]
+    SkinMismatch { vertex: usize, kind: DdmKind },
+    #[error("Bone {bone} has id {id} already used by bone {first_bone}")]
+    DuplicateBoneId { bone: usize, first_bone: usize, id: u32 },
+}
+
+impl DdmDiagnostic {
+    /// Returns true if diagnostic would cause out of bounds access when reading geometry
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            DdmDiagnostic::FaceGroupOutOfRange { .. }
+                | DdmDiagnostic::TriangleIndexOutOfRange { .. }
+                | DdmDiagnostic::PaletteIndexOutOfRange { .. }
+        )
+    }
+}
+
+impl DdmFile {
+    pub fn validate(&self) -> Vec<DdmDiagnostic> {
+        let mut diagnostics = Vec::new();
+
+        // Check face groups
+        for (mesh_idx, mesh) in self.meshes.iter().enumerate() {
+            for (group_idx, group) in mesh.face_groups.iter().enumerate() {
+                // 3 indicies = 1 triangle
+                let start = group.triangle_start_idx as usize;
+                let end = start + (group.triangle_count as usize * 3);
+
+                if end > self.triangles.len() {
+                    diagnostics.push(DdmDiagnostic::FaceGroupOutOfRange {
+                        mesh: mesh_idx,
+                        group: group_idx,
+                        start,
+                        end,
+                        len: self.triangles.len(),
+                    });
+                }
+
+                if !self.kind.is_skinned() {
+                    continue;
+                }
+
+                for (slot, index) in group.palette().iter().enumerate() {
+                    if (*index as usize) < self.bones.len() {
+                        continue;
+                    }
+
+                    diagnostics.push(DdmDiagnostic::PaletteIndexOutOfRange {
+                        mesh: mesh_idx,
+                        group: group_idx,
+                        slot,
+                        index: *index,
+                        bone_count: self.bones.len(),
+                    });
+                }
+            }
+        }
+
+        // Check triangles
+        for (position, index) in self.triangles.iter().enumerate() {
+            if (*index as usize) < self.vertices.len() {
+                continue;
+            }
+
+            diagnostics.push(DdmDiagnostic::TriangleIndexOutOfRange {
+                position,
+                index: *index,
+                vertex_count: self.vertices.len(),
+            });
+        }
+
+        // Check vertices
+        for (vertex, v) in self.vertices.iter().enumerate() {
+            validate_vertex(vertex, v, self.kind, &mut diagnostics);
+        }
+
+        // Check bones
+        let mut bone_ids = HashMap::new();
+        for (bone, b) in self.bones.iter().enumerate() {
+            if let Some(first_bone) = bone_ids.insert(b.id, bone) {
+                diagnostics.push(DdmDiagnostic::DuplicateBoneId {
+                    bone,
+                    first_bone,
+                    id: b.id,
+                });
+
+                // Keep reporting against first occurrence
+                bone_ids.insert(b.id, first_bone);
+            }
+        }
+
+        diagnostics
+    }
+}
+
+fn validate_vertex(vertex: usize, v: &DdmVertex, kind: DdmKind, diagnostics: &mut Vec<DdmDiagnostic>) {
+    if [v.x, v.y, v.z].iter().any(|p| !p.is_finite()) {
+        diagnostics.push(DdmDiagnostic::NonFinitePosition { vertex });
+    }
+
+    let length = (v.nx * v.nx + v.ny * v.ny + v.nz * v.nz).sqrt();
+    if !is_near_one(length, NORMAL_LENGTH_TOLERANCE) {
+        diagnostics.push(DdmDiagnostic::NonUnitNormal { vertex, length });
+    }
+
+    // Skin data is dropped or zeroed when written as other kind
+    let skin = match (&v.skin, kind.is_skinned()) {
+        (Some(skin), true) => skin,
+        (None, false) => return,
+        _ => {
+            diagnostics.push(DdmDiagnostic::SkinMismatch { vertex, kind });
+            return;
+        }
+    };
+
+    for value in skin.bones {
+        if value.fract() != 0.0 || value < 0.0 {
+            diagnostics.push(DdmDiagnostic::NonIntegralBoneIndex { vertex, value });
+        }
+    }
+
+    let sum: f32 = skin.weights.iter().sum();
+    if !is_near_one(sum, WEIGHT_SUM_TOLERANCE) {
+        diagnostics.push(DdmDiagnostic::InvalidWeightSum { vertex, sum });
+    }
+}
+
+fn is_near_one(value: f32, tolerance: f32) -> bool {
+    // Also false for NaN
+    (1.0 - value).abs() <= tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddm::tests::{create_face_group, create_mesh_ddm, create_skinned_vertex};
+    use crate::DdmBone;
+
+    fn create_valid_ddm() -> DdmFile {
+        let vertex = DdmVertex {
+            nz: 1.0,
+            ..create_skinned_vertex([0.0, 1.0, 0.0, 0.0], [0.25, 0.75, 0.0, 0.0])
+        };
+
+        create_mesh_ddm(
+            DdmKind::Skinned,
+            vec![create_face_group(&[0, 1], 0, 1)],
+            (0..2).map(|id| DdmBone { id, ..Default::default() }).collect(),
+            vec![0, 1, 2],
+            vec![vertex; 3],
+        )
+    }
+
+    #[test]
+    fn validate_valid_test() {
+        let ddm = create_valid_ddm();
+        assert_eq!(Vec::<DdmDiagnostic>::new(), ddm.validate());
+    }
+
+    #[test]
+    fn validate_out_of_range_test() {
+        let mut ddm = create_valid_ddm();
+        ddm.meshes[0].face_groups[0].triangle_count = 2;
+        ddm.meshes[0].face_groups[0].indicies[1] = 5;
+        ddm.triangles[2] = 3;
+
+        let diagnostics = ddm.validate();
+        assert_eq!(
+            vec![
+                DdmDiagnostic::FaceGroupOutOfRange { mesh: 0, group: 0, start: 0, end: 6, len: 3 },
+                DdmDiagnostic::PaletteIndexOutOfRange { mesh: 0, group: 0, slot: 1, index: 5, bone_count: 2 },
+                DdmDiagnostic::TriangleIndexOutOfRange { position: 2, index: 3, vertex_count: 3 },
+            ],
+            diagnostics
+        );
+        assert!(diagnostics.iter().all(|d| d.is_error()));
+    }
+
+    #[test]
+    fn validate_vertex_test() {
+        let mut ddm = create_valid_ddm();
+        ddm.vertices[0].x = f32::NAN;
+        ddm.vertices[1].nz = 0.5;
+        ddm.vertices[1].skin.as_mut().unwrap().bones[2] = 1.5;
+        ddm.vertices[2].skin.as_mut().unwrap().weights[3] = 1.0;
+        ddm.bones[1].id = 0;
+
+        let diagnostics = ddm.validate();
+        assert_eq!(
+            vec![
+                DdmDiagnostic::NonFinitePosition { vertex: 0 },
+                DdmDiagnostic::NonUnitNormal { vertex: 1, length: 0.5 },
+                DdmDiagnostic::NonIntegralBoneIndex { vertex: 1, value: 1.5 },
+                DdmDiagnostic::InvalidWeightSum { vertex: 2, sum: 2.0 },
+                DdmDiagnostic::DuplicateBoneId { bone: 1, first_bone: 0, id: 0 },
+            ],
+            diagnostics
+        );
+        assert!(diagnostics.iter().all(|d| !d.is_error()));
+    }
+
+    #[test]
+    fn validate_skin_mismatch_test() {
+        let mut ddm = create_valid_ddm();
+        ddm.vertices[0].skin = None;
+        assert_eq!(vec![DdmDiagnostic::SkinMismatch { vertex: 0, kind: DdmKind::Skinned }], ddm.validate());
+
+        ddm.kind = DdmKind::Static;
+        assert_eq!(
+            vec![
+                DdmDiagnostic::SkinMismatch { vertex: 1, kind: DdmKind::Static },
+                DdmDiagnostic::SkinMismatch { vertex: 2, kind: DdmKind::Static },
+            ],
+            ddm.validate()
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,211 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, LitInt, LitStr, Type};
+
+#[derive(Default)]
+struct FieldOptions {
+    string: Option<LitInt>,   // Fixed size null-padded string
+    pad: Option<LitInt>,      // Bytes to skip before field
+    count: Option<Type>,      // Count prefix type for vec
+    cond: Option<Expr>,       // Field is only present when true, otherwise default
+}
+
+struct BinField {
+    ident: Ident,
+    ty: Type,
+    options: FieldOptions,
+}
+
+struct BinStruct {
+    ident: Ident,
+    ctx: TokenStream2,
+    fields: Vec<BinField>,
+}
+
+fn parse_struct(input: &DeriveInput) -> syn::Result<BinStruct> {
+    let mut ctx = quote!(());
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("offbeat")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ctx") {
+                let ty: Type = meta.value()?.parse()?;
+                ctx = quote!(#ty);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported struct attribute"))
+            }
+        })?;
+    }
+
+    let named_fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "only structs with named fields are supported")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "only structs are supported")),
+    };
+
+    let mut fields = Vec::new();
+    for field in named_fields.iter() {
+        let mut options = FieldOptions::default();
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("offbeat")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("string") {
+                    options.string = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("pad") {
+                    options.pad = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("count") {
+                    options.count = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("cond") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    options.cond = Some(lit.parse()?);
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        fields.push(BinField {
+            ident: field.ident.clone().unwrap(),
+            ty: field.ty.clone(),
+            options,
+        });
+    }
+
+    Ok(BinStruct {
+        ident: input.ident.clone(),
+        ctx,
+        fields,
+    })
+}
+
+fn read_field(field: &BinField, ctx: &TokenStream2) -> TokenStream2 {
+    let BinField { ident, ty, options } = field;
+
+    let mut read = if let Some(size) = &options.string {
+        quote!(reader.read_string::<#size>()?)
+    } else if let Some(count_ty) = &options.count {
+        quote!({
+            let count = reader.read::<#count_ty>()? as usize;
+            let mut items = Vec::new();
+            for i in 0..count {
+                let item = offbeat::BinRead::<#ctx>::bin_read(reader, ctx)
+                    .map_err(|err| offbeat::OffbeatError::at_index(i, err))?;
+                items.push(item);
+            }
+            items
+        })
+    } else {
+        quote!(<#ty as offbeat::BinRead<#ctx>>::bin_read(reader, ctx)?)
+    };
+
+    if let Some(pad) = &options.pad {
+        read = quote!({
+            reader.skip(#pad)?;
+            #read
+        });
+    }
+
+    if let Some(cond) = &options.cond {
+        read = quote!(if #cond { #read } else { Default::default() });
+    }
+
+    // Tag errors with field name + offset
+    let name = ident.to_string();
+    quote!(
+        let offset = reader.position()?;
+        let #ident: #ty = (|| -> Result<#ty, offbeat::OffbeatError> { Ok(#read) })()
+            .map_err(|err| offbeat::OffbeatError::at_field(#name, offset, err))?;
+    )
+}
+
+fn write_field(field: &BinField, ctx: &TokenStream2) -> TokenStream2 {
+    let BinField { ident, ty, options } = field;
+
+    let mut write = if let Some(size) = &options.string {
+        quote!(writer.write_raw_string::<#size>(&self.#ident)?;)
+    } else if let Some(count_ty) = &options.count {
+        quote!(
+            writer.write(&(self.#ident.len() as #count_ty))?;
+            for item in self.#ident.iter() {
+                offbeat::BinWrite::<#ctx>::bin_write(item, writer, ctx)?;
+            }
+        )
+    } else {
+        quote!(<#ty as offbeat::BinWrite<#ctx>>::bin_write(&self.#ident, writer, ctx)?;)
+    };
+
+    if let Some(pad) = &options.pad {
+        write = quote!(
+            writer.write_bytes(&[0u8; #pad])?;
+            #write
+        );
+    }
+
+    if let Some(cond) = &options.cond {
+        write = quote!(if #cond { #write });
+    }
+
+    write
+}
+
+/// Derives `offbeat::BinRead` by reading fields in declaration order
+///
+/// Field attributes: `#[offbeat(string = 64)]`, `#[offbeat(pad = 4)]`,
+/// `#[offbeat(count = u32)]` and `#[offbeat(cond = "ctx.is_skinned")]`.
+/// Context type is set with `#[offbeat(ctx = Type)]` on struct.
+#[proc_macro_derive(BinRead, attributes(offbeat))]
+pub fn derive_bin_read(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let bin_struct = match parse_struct(&input) {
+        Ok(s) => s,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let BinStruct { ident, ctx, fields } = &bin_struct;
+    let reads = fields.iter().map(|f| read_field(f, ctx));
+    let idents = fields.iter().map(|f| &f.ident);
+
+    quote!(
+        impl offbeat::BinRead<#ctx> for #ident {
+            #[allow(unused_variables, clippy::needless_question_mark)]
+            fn bin_read<T: std::io::Read + std::io::Seek>(reader: &mut offbeat::ByteReader<T>, ctx: &#ctx) -> Result<Self, offbeat::OffbeatError> {
+                #(#reads)*
+
+                Ok(Self {
+                    #(#idents),*
+                })
+            }
+        }
+    ).into()
+}
+
+/// Derives `offbeat::BinWrite` by writing fields in declaration order
+///
+/// Supports same attributes as `BinRead`.
+#[proc_macro_derive(BinWrite, attributes(offbeat))]
+pub fn derive_bin_write(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let bin_struct = match parse_struct(&input) {
+        Ok(s) => s,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let BinStruct { ident, ctx, fields } = &bin_struct;
+    let writes = fields.iter().map(|f| write_field(f, ctx));
+
+    quote!(
+        impl offbeat::BinWrite<#ctx> for #ident {
+            #[allow(unused_variables)]
+            fn bin_write<T: std::io::Write + std::io::Seek>(&self, writer: &mut offbeat::ByteWriter<T>, ctx: &#ctx) -> Result<(), offbeat::OffbeatError> {
+                #(#writes)*
+
+                Ok(())
+            }
+        }
+    ).into()
+}
\ No newline at end of file